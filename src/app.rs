@@ -1,22 +1,59 @@
-use crate::config::Config;
-use crate::input::{KeyEvent, KeyListener, ListenerConfig};
+use crate::compositor::LayoutEvent;
+use crate::config::{Action, Config};
+use crate::input::layout::LayoutManager;
+use crate::input::{
+    init_key_display_map, Keybind, KeyEvent, KeyListener, KeyMapConfig, ListenerConfig, Mods,
+};
 use crate::ui::{
-    create_launcher_window, create_window, setup_drag, show_launcher, DisplayMode, KeyDisplayWidget,
+    apply_runtime_config, create_launcher_window, create_settings_window, create_window,
+    follow_focused_output, setup_drag, show_launcher, show_settings, BubbleDisplayWidget,
+    DisplayMode, KeyDisplayWidget,
 };
 use anyhow::Result;
-use async_channel::{bounded, Receiver};
+use async_channel::{bounded, Receiver, Sender};
 use glib::ControlFlow;
 use gtk4::prelude::*;
 use gtk4::{Application, ApplicationWindow};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::time::Duration;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// A runtime command delivered to the running application, typically from the
+/// system tray via [`App::command_sender`].
+#[derive(Debug, Clone)]
+pub enum Command {
+    ShowLauncher,
+
+    SetMode(DisplayMode),
+
+    TogglePause,
+
+    OpenSettings,
+}
+
+/// A notification pushed out of the running application, so the tray icon
+/// stays in sync with mode/pause changes that didn't originate from the tray
+/// menu itself (the launcher, a keybind).
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    ModeChanged(DisplayMode),
+
+    PauseChanged(bool),
+}
 
 pub struct App {
     gtk_app: Application,
 
     config: Config,
+
+    command_tx: Sender<Command>,
+
+    command_rx: Option<Receiver<Command>>,
+
+    event_tx: std::sync::mpsc::Sender<AppEvent>,
+
+    event_rx: Option<std::sync::mpsc::Receiver<AppEvent>>,
 }
 
 struct RuntimeState {
@@ -24,18 +61,33 @@ struct RuntimeState {
 
     paused: bool,
 
-    keystroke_window: Option<ApplicationWindow>,
+    display_window: Option<ApplicationWindow>,
 
     launcher_window: Option<ApplicationWindow>,
+
+    settings_window: Option<ApplicationWindow>,
+
+    display: Option<Rc<RefCell<KeyDisplayWidget>>>,
+
+    bubble: Option<Rc<RefCell<BubbleDisplayWidget>>>,
+
+    layout: Option<LayoutManager>,
+
+    event_tx: std::sync::mpsc::Sender<AppEvent>,
 }
 
-impl Default for RuntimeState {
-    fn default() -> Self {
+impl RuntimeState {
+    fn new(event_tx: std::sync::mpsc::Sender<AppEvent>) -> Self {
         Self {
             mode: None,
             paused: false,
-            keystroke_window: None,
+            display_window: None,
             launcher_window: None,
+            settings_window: None,
+            display: None,
+            bubble: None,
+            layout: None,
+            event_tx,
         }
     }
 }
@@ -46,14 +98,39 @@ impl App {
             .application_id("dev.linuxmobile.keystroke")
             .build();
 
-        Ok(Self { gtk_app, config })
+        let (command_tx, command_rx) = bounded(64);
+        let (event_tx, event_rx) = std::sync::mpsc::channel();
+
+        Ok(Self {
+            gtk_app,
+            config,
+            command_tx,
+            command_rx: Some(command_rx),
+            event_tx,
+            event_rx: Some(event_rx),
+        })
     }
 
-    pub fn run(self) -> Result<i32> {
+    /// Hand out a sender for delivering [`Command`]s to the running app. Call
+    /// this before [`App::run`]; the tray pump in `main` forwards actions here.
+    pub fn command_sender(&self) -> Sender<Command> {
+        self.command_tx.clone()
+    }
+
+    /// Hand out the receiving end of [`AppEvent`] notifications. Call this
+    /// before [`App::run`]; `main` forwards them to the tray handle so its icon
+    /// reflects mode/pause changes the app made on its own.
+    pub fn event_receiver(&mut self) -> std::sync::mpsc::Receiver<AppEvent> {
+        self.event_rx.take().expect("event receiver already taken")
+    }
+
+    pub fn run(mut self) -> Result<i32> {
         let config = self.config.clone();
+        let command_rx = self.command_rx.take();
+        let event_tx = self.event_tx.clone();
 
         self.gtk_app.connect_activate(move |app| {
-            if let Err(e) = activate(app, &config) {
+            if let Err(e) = activate(app, &config, command_rx.clone(), event_tx.clone()) {
                 error!("Failed to activate application: {}", e);
             }
         });
@@ -64,10 +141,19 @@ impl App {
     }
 }
 
-fn activate(app: &Application, config: &Config) -> Result<()> {
+fn activate(
+    app: &Application,
+    config: &Config,
+    command_rx: Option<Receiver<Command>>,
+    event_tx: std::sync::mpsc::Sender<AppEvent>,
+) -> Result<()> {
     info!("Activating keystroke application");
 
-    let state = Rc::new(RefCell::new(RuntimeState::default()));
+    // Merge any user key-display overrides over the built-in glyph table before
+    // the first key is rendered.
+    init_key_display_map(KeyMapConfig::load_or_default());
+
+    let state = Rc::new(RefCell::new(RuntimeState::new(event_tx)));
     let config = Rc::new(config.clone());
 
     let state_clone = Rc::clone(&state);
@@ -77,32 +163,186 @@ fn activate(app: &Application, config: &Config) -> Result<()> {
     let launcher = create_launcher_window(app, move |mode| {
         debug!("Mode selected: {:?}", mode);
 
-        state_clone.borrow_mut().mode = Some(mode);
+        if let Err(e) = start_display_mode(&app_clone, &config_clone, Rc::clone(&state_clone), mode)
+        {
+            error!("Failed to start {:?} mode: {}", mode, e);
+        }
+    });
+
+    state.borrow_mut().launcher_window = Some(launcher.clone());
+
+    show_launcher(&launcher);
+
+    if let Some(command_rx) = command_rx {
+        setup_command_processing(app.clone(), Rc::clone(&config), Rc::clone(&state), command_rx);
+    }
+
+    setup_config_watch(Rc::clone(&state));
+
+    setup_layout_tracking(Rc::clone(&state));
+
+    Ok(())
+}
+
+/// Instantiate a [`LayoutManager`], seed the overlay's layout badge, and stream
+/// live [`LayoutEvent`]s into the GTK main context through an `async_channel`.
+/// Unsupported compositors are left untouched, and no badge is shown until a
+/// layout is actually reported.
+fn setup_layout_tracking(state: Rc<RefCell<RuntimeState>>) {
+    let mut manager = LayoutManager::new();
+
+    if !manager.supports_layout_query() {
+        debug!(
+            "Compositor {} has no layout query support; skipping layout badge",
+            manager.compositor()
+        );
+        return;
+    }
 
-        match mode {
-            DisplayMode::Keystroke => {
-                if let Err(e) =
-                    start_keystroke_mode(&app_clone, &config_clone, Rc::clone(&state_clone))
-                {
-                    error!("Failed to start keystroke mode: {}", e);
+    if let Err(e) = manager.init() {
+        warn!("Failed to read keyboard layouts: {}", e);
+    }
+
+    let (tx, rx) = bounded::<LayoutEvent>(16);
+
+    manager.start_listener(move |event| {
+        // Hand the event to the GTK side; drop it if the drain has gone away.
+        let _ = tx.send_blocking(event);
+    });
+
+    state.borrow_mut().layout = Some(manager);
+
+    glib::timeout_add_local(Duration::from_millis(150), move || {
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                LayoutEvent::LayoutsChanged { layouts } => match layouts.current_name() {
+                    Some(name) => set_layout_badge(&state, name),
+                    None => set_layout_badge(&state, ""),
+                },
+                LayoutEvent::LayoutSwitched { name, .. } => {
+                    set_layout_badge(&state, &name);
+                    flash_overlay(&state);
                 }
             }
-            DisplayMode::Bubble => {
-                info!("Bubble mode selected (using keystroke for now)");
-                if let Err(e) =
-                    start_keystroke_mode(&app_clone, &config_clone, Rc::clone(&state_clone))
-                {
-                    error!("Failed to start bubble mode: {}", e);
-                }
+        }
+
+        ControlFlow::Continue
+    });
+}
+
+/// Collapse an XKB source id (`us+dvorak`, `de`) to the compact badge shown in
+/// the overlay (`US`, `DE`).
+fn layout_badge(name: &str) -> String {
+    name.split('+').next().unwrap_or(name).to_uppercase()
+}
+
+/// Push the current layout onto the active overlay's badge, hiding it when
+/// `name` is empty. A no-op while no keystroke window is up.
+fn set_layout_badge(state: &Rc<RefCell<RuntimeState>>, name: &str) {
+    if let Some(display) = &state.borrow().display {
+        let badge = if name.is_empty() {
+            String::new()
+        } else {
+            layout_badge(name)
+        };
+        display.borrow().set_layout_indicator(&badge);
+    }
+}
+
+/// Flash the overlay on a layout switch so the change is noticeable even when
+/// no keys are being pressed.
+fn flash_overlay(state: &Rc<RefCell<RuntimeState>>) {
+    let state = state.borrow();
+    if let Some(display) = &state.display {
+        display.borrow_mut().flash();
+    }
+    if let Some(window) = &state.display_window {
+        window.set_visible(true);
+    }
+}
+
+/// Spawn the config-file watcher and apply each live update to the running
+/// overlay: re-anchor and re-opacity the window, and push the new `max_keys`
+/// and timeout into the active [`KeyDisplayWidget`]. Invalid reloads never reach
+/// here — [`Config::watch`] drops them and keeps the last good config.
+fn setup_config_watch(state: Rc<RefCell<RuntimeState>>) {
+    let (tx, rx) = bounded::<Config>(8);
+
+    if let Err(e) = Config::watch(tx) {
+        warn!("Config hot-reload disabled: {}", e);
+        return;
+    }
+
+    glib::timeout_add_local(Duration::from_millis(200), move || {
+        while let Ok(config) = rx.try_recv() {
+            let state = state.borrow();
+
+            if let Some(window) = &state.display_window {
+                apply_runtime_config(window, &config);
+            }
+
+            if let Some(display) = &state.display {
+                let mut display = display.borrow_mut();
+                display.set_max_keys(config.max_keys);
+                display.set_display_timeout(config.display_timeout_ms);
             }
         }
+
+        ControlFlow::Continue
     });
+}
 
-    state.borrow_mut().launcher_window = Some(launcher.clone());
+/// Drain runtime commands (from the tray) and apply them to the live app:
+/// re-presenting the launcher, swapping the display mode, or toggling pause.
+fn setup_command_processing(
+    app: Application,
+    config: Rc<Config>,
+    state: Rc<RefCell<RuntimeState>>,
+    command_rx: Receiver<Command>,
+) {
+    glib::timeout_add_local(Duration::from_millis(100), move || {
+        while let Ok(command) = command_rx.try_recv() {
+            match command {
+                Command::ShowLauncher => {
+                    show_launcher_from_state(&state);
+                }
+                Command::SetMode(mode) => {
+                    info!("Switching display mode to {:?}", mode);
+                    if let Err(e) = start_display_mode(&app, &config, Rc::clone(&state), mode) {
+                        error!("Failed to switch display mode: {}", e);
+                    }
+                }
+                Command::TogglePause => {
+                    let paused = toggle_pause(&state);
+                    info!("Display {}", if paused { "paused" } else { "resumed" });
+                }
+                Command::OpenSettings => {
+                    open_settings_window(&app, &config, &state);
+                }
+            }
+        }
 
-    show_launcher(&launcher);
+        ControlFlow::Continue
+    });
+}
 
-    Ok(())
+/// Start (or switch to) the given [`DisplayMode`], tearing down whichever
+/// surface was previously active, and notify the tray so its icon tracks the
+/// mode even when the switch was driven by the launcher or a keybind rather
+/// than the tray menu.
+fn start_display_mode(
+    app: &Application,
+    config: &Config,
+    state: Rc<RefCell<RuntimeState>>,
+    mode: DisplayMode,
+) -> Result<()> {
+    state.borrow_mut().mode = Some(mode);
+    let _ = state.borrow().event_tx.send(AppEvent::ModeChanged(mode));
+
+    match mode {
+        DisplayMode::Keystroke => start_keystroke_mode(app, config, state),
+        DisplayMode::Bubble => start_bubble_mode(app, config, state),
+    }
 }
 
 fn start_keystroke_mode(
@@ -112,21 +352,32 @@ fn start_keystroke_mode(
 ) -> Result<()> {
     info!("Starting keystroke mode");
 
-    if let Some(window) = state.borrow_mut().keystroke_window.take() {
+    if let Some(window) = state.borrow_mut().display_window.take() {
         window.close();
     }
 
     let window = create_window(app, config)?;
 
-    setup_drag(&window);
+    setup_drag(&window, config);
+
+    follow_focused_output(&window, config);
 
     let display = Rc::new(RefCell::new(KeyDisplayWidget::new(
         config.max_keys,
         config.display_timeout_ms,
+        config.chord_coalesce_ms,
     )));
 
     window.set_child(Some(display.borrow().widget()));
 
+    // Carry the current layout badge onto the freshly created widget so it
+    // persists across mode switches rather than waiting for the next event.
+    if let Some(manager) = &state.borrow().layout {
+        if let Some(name) = manager.current_layout_name() {
+            display.borrow().set_layout_indicator(&layout_badge(&name));
+        }
+    }
+
     let (sender, receiver) = bounded::<KeyEvent>(256);
 
     let listener_config = ListenerConfig {
@@ -143,49 +394,285 @@ fn start_keystroke_mode(
         window.set_child(Some(&error_label));
     } else {
         let state_clone = Rc::clone(&state);
-        setup_event_processing(display.clone(), receiver, state_clone);
+        setup_event_processing(
+            app.clone(),
+            config.clone(),
+            display.clone(),
+            receiver,
+            state_clone,
+        );
 
         let state_clone = Rc::clone(&state);
         setup_cleanup_timer(display.clone(), window.clone(), state_clone);
     }
 
-    state.borrow_mut().keystroke_window = Some(window.clone());
+    state.borrow_mut().bubble = None;
+    state.borrow_mut().display_window = Some(window.clone());
+    state.borrow_mut().display = Some(display.clone());
 
     window.present();
 
     Ok(())
 }
 
-fn setup_event_processing(
-    display: Rc<RefCell<KeyDisplayWidget>>,
+/// Mirror of [`start_keystroke_mode`] for [`DisplayMode::Bubble`]: builds a
+/// [`BubbleDisplayWidget`] in place of the [`KeyDisplayWidget`] and feeds it
+/// the same [`KeyEvent`] stream.
+fn start_bubble_mode(
+    app: &Application,
+    config: &Config,
+    state: Rc<RefCell<RuntimeState>>,
+) -> Result<()> {
+    info!("Starting bubble mode");
+
+    if let Some(window) = state.borrow_mut().display_window.take() {
+        window.close();
+    }
+
+    let window = create_window(app, config)?;
+
+    setup_drag(&window, config);
+
+    follow_focused_output(&window, config);
+
+    let bubble = Rc::new(RefCell::new(BubbleDisplayWidget::new(
+        config.bubble_timeout_ms,
+        config,
+    )));
+
+    window.set_child(Some(bubble.borrow().widget()));
+
+    let (sender, receiver) = bounded::<KeyEvent>(256);
+
+    let listener_config = ListenerConfig {
+        all_keyboards: config.all_keyboards,
+        ..Default::default()
+    };
+
+    let listener = KeyListener::new(sender, listener_config);
+
+    if let Err(e) = listener.start() {
+        error!("Failed to start key listener: {}", e);
+
+        let error_label = gtk4::Label::new(Some(&format!("Error: {}", e)));
+        window.set_child(Some(&error_label));
+    } else {
+        let state_clone = Rc::clone(&state);
+        setup_bubble_event_processing(
+            app.clone(),
+            config.clone(),
+            bubble.clone(),
+            receiver,
+            state_clone,
+        );
+
+        let state_clone = Rc::clone(&state);
+        setup_bubble_cleanup_timer(bubble.clone(), window.clone(), state_clone);
+    }
+
+    state.borrow_mut().display = None;
+    state.borrow_mut().display_window = Some(window.clone());
+    state.borrow_mut().bubble = Some(bubble.clone());
+
+    window.present();
+
+    Ok(())
+}
+
+/// Open the settings window, closing any previous instance first — mirrors
+/// how [`start_keystroke_mode`]/[`start_bubble_mode`] recreate their window
+/// rather than trying to re-present a closed one.
+fn open_settings_window(app: &Application, config: &Config, state: &Rc<RefCell<RuntimeState>>) {
+    if let Some(window) = state.borrow_mut().settings_window.take() {
+        window.close();
+    }
+
+    let config_ref = Rc::new(RefCell::new(config.clone()));
+    let window = create_settings_window(app, config_ref, |_| {});
+
+    state.borrow_mut().settings_window = Some(window.clone());
+
+    show_settings(&window);
+}
+
+fn setup_bubble_event_processing(
+    app: Application,
+    config: Config,
+    bubble: Rc<RefCell<BubbleDisplayWidget>>,
     receiver: Receiver<KeyEvent>,
     state: Rc<RefCell<RuntimeState>>,
 ) {
+    let keybinds = parse_keybinds(&config.keybinds);
+    let mut mods = Mods::default();
+
     glib::timeout_add_local(Duration::from_millis(16), move || {
+        let paused = state.borrow().paused;
+
+        let mut actions = Vec::new();
+
+        while let Ok(event) = receiver.try_recv() {
+            match event {
+                KeyEvent::Pressed(key) => {
+                    mods.update(key.key, true);
+                    if let Some(action) = match_keybind(&keybinds, mods, key.key) {
+                        actions.push(action);
+                    }
+                    if !paused {
+                        bubble.borrow_mut().process_key(key);
+                    }
+                }
+                KeyEvent::Released(key) => {
+                    mods.update(key.key, false);
+                    if !paused {
+                        bubble.borrow_mut().process_key_release(key);
+                    }
+                }
+                KeyEvent::AllReleased => {
+                    mods = Mods::default();
+                    if !paused {
+                        bubble.borrow_mut().clear();
+                    }
+                }
+            }
+        }
+
+        for action in actions {
+            dispatch_action(action, &app, &config, &state);
+        }
+
+        ControlFlow::Continue
+    });
+}
+
+fn setup_bubble_cleanup_timer(
+    bubble: Rc<RefCell<BubbleDisplayWidget>>,
+    window: ApplicationWindow,
+    state: Rc<RefCell<RuntimeState>>,
+) {
+    glib::timeout_add_local(Duration::from_millis(100), move || {
         if state.borrow().paused {
             return ControlFlow::Continue;
         }
 
-        while let Ok(event) = receiver.try_recv() {
-            let mut display = display.borrow_mut();
+        let mut bubble = bubble.borrow_mut();
+        bubble.remove_expired();
 
+        window.set_visible(bubble.should_show());
+
+        ControlFlow::Continue
+    });
+}
+
+fn setup_event_processing(
+    app: Application,
+    config: Config,
+    display: Rc<RefCell<KeyDisplayWidget>>,
+    receiver: Receiver<KeyEvent>,
+    state: Rc<RefCell<RuntimeState>>,
+) {
+    let keybinds = parse_keybinds(&config.keybinds);
+    let mut mods = Mods::default();
+
+    glib::timeout_add_local(Duration::from_millis(16), move || {
+        let paused = state.borrow().paused;
+
+        // Keybinds are matched even while paused so a bound chord can resume the
+        // overlay; only the rendering of keystrokes honours the pause flag.
+        let mut actions = Vec::new();
+
+        while let Ok(event) = receiver.try_recv() {
             match event {
                 KeyEvent::Pressed(key) => {
-                    display.add_key(key);
+                    mods.update(key.key, true);
+                    if let Some(action) = match_keybind(&keybinds, mods, key.key) {
+                        actions.push(action);
+                    }
+                    if !paused {
+                        display.borrow_mut().add_key(key);
+                    }
                 }
                 KeyEvent::Released(key) => {
-                    display.remove_key(&key);
+                    mods.update(key.key, false);
+                    if !paused {
+                        display.borrow_mut().remove_key(&key);
+                    }
                 }
                 KeyEvent::AllReleased => {
-                    display.clear();
+                    mods = Mods::default();
+                    if !paused {
+                        display.borrow_mut().clear();
+                    }
                 }
             }
         }
 
+        for action in actions {
+            dispatch_action(action, &app, &config, &state);
+        }
+
         ControlFlow::Continue
     });
 }
 
+/// Parse each configured chord string into a [`Keybind`], pairing it with its
+/// [`Action`]. Chords that don't parse are logged and dropped rather than
+/// aborting startup, mirroring how the rest of the config tolerates bad input.
+fn parse_keybinds(map: &std::collections::HashMap<String, Action>) -> Vec<(Keybind, Action)> {
+    let mut binds = Vec::new();
+    for (chord, action) in map {
+        match Keybind::parse(chord) {
+            Some(bind) => binds.push((bind, *action)),
+            None => warn!("Ignoring unparseable keybind chord: {:?}", chord),
+        }
+    }
+    binds
+}
+
+/// Return the action bound to the just-pressed `key` under the current modifier
+/// state, if any. The first matching binding wins.
+fn match_keybind(binds: &[(Keybind, Action)], mods: Mods, key: evdev::Key) -> Option<Action> {
+    binds
+        .iter()
+        .find(|(bind, _)| bind.key == key && bind.mods == mods)
+        .map(|(_, action)| *action)
+}
+
+/// Apply a keybind-triggered [`Action`] to the running app, reusing the same
+/// [`RuntimeState`] mutations the tray commands drive.
+fn dispatch_action(action: Action, app: &Application, config: &Config, state: &Rc<RefCell<RuntimeState>>) {
+    match action {
+        Action::TogglePause => {
+            let paused = toggle_pause(state);
+            info!("Display {}", if paused { "paused" } else { "resumed" });
+        }
+        Action::ShowLauncher => {
+            show_launcher_from_state(state);
+        }
+        Action::SwitchMode => {
+            let next = match state.borrow().mode {
+                Some(DisplayMode::Keystroke) => DisplayMode::Bubble,
+                _ => DisplayMode::Keystroke,
+            };
+            if let Err(e) = start_display_mode(app, config, Rc::clone(state), next) {
+                error!("Failed to switch display mode: {}", e);
+            }
+        }
+        Action::Quit => {
+            info!("Quit requested via keybind");
+            app.quit();
+        }
+        Action::Clear => {
+            let state = state.borrow();
+            if let Some(display) = &state.display {
+                display.borrow_mut().clear();
+            }
+            if let Some(bubble) = &state.bubble {
+                bubble.borrow_mut().clear();
+            }
+        }
+    }
+}
+
 fn setup_cleanup_timer(
     display: Rc<RefCell<KeyDisplayWidget>>,
     window: ApplicationWindow,
@@ -199,24 +686,24 @@ fn setup_cleanup_timer(
         let mut display = display.borrow_mut();
         display.remove_expired();
 
-        if !display.has_keys() {
-            window.set_visible(false);
-        } else {
+        if display.has_keys() || display.is_flashing() {
             window.set_visible(true);
+        } else {
+            window.set_visible(false);
         }
 
         ControlFlow::Continue
     });
 }
 
-#[allow(dead_code)]
 fn toggle_pause(state: &Rc<RefCell<RuntimeState>>) -> bool {
     let mut s = state.borrow_mut();
     s.paused = !s.paused;
-    s.paused
+    let paused = s.paused;
+    let _ = s.event_tx.send(AppEvent::PauseChanged(paused));
+    paused
 }
 
-#[allow(dead_code)]
 fn show_launcher_from_state(state: &Rc<RefCell<RuntimeState>>) {
     if let Some(launcher) = &state.borrow().launcher_window {
         show_launcher(launcher);
@@ -0,0 +1,125 @@
+use super::{CompositorClient, KeyboardLayouts};
+use std::env;
+use std::process::Command;
+
+/// dconf path holding the ordered list of GNOME input sources.
+const SOURCES_KEY: &str = "/org/gnome/desktop/input-sources/sources";
+
+#[derive(Debug)]
+pub struct GnomeClient {
+    // Inside a Flatpak sandbox dconf lives on the host, reached via
+    // `flatpak-spawn --host`.
+    flatpak: bool,
+}
+
+impl GnomeClient {
+    #[must_use]
+    pub fn new() -> Option<Self> {
+        let client = Self {
+            flatpak: env::var_os("container").is_some(),
+        };
+
+        if client.is_available() {
+            Some(client)
+        } else {
+            tracing::debug!("GNOME input-sources not available");
+            None
+        }
+    }
+
+    /// Read the raw GVariant value of the input-sources key, shelling out to
+    /// `flatpak-spawn --host` when running inside a sandbox.
+    fn read_sources(&self) -> anyhow::Result<String> {
+        let output = if self.flatpak {
+            Command::new("flatpak-spawn")
+                .args(["--host", "dconf", "read", SOURCES_KEY])
+                .output()?
+        } else {
+            Command::new("dconf").args(["read", SOURCES_KEY]).output()?
+        };
+
+        if !output.status.success() {
+            anyhow::bail!("dconf read {} failed", SOURCES_KEY);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Parse a dconf GVariant array of `(type, id)` tuples, e.g.
+    /// `[('xkb', 'us+dvorak'), ('xkb', 'de')]`. Only `xkb` entries are kept;
+    /// the id is passed through verbatim (`us+dvorak`, `de`) so the existing
+    /// `parse_layout_name`/XKB machinery can split it into layout and variant.
+    fn parse_sources(&self, variant: &str) -> KeyboardLayouts {
+        let mut layouts = KeyboardLayouts::default();
+
+        let mut rest = variant;
+        while let Some(open) = rest.find('(') {
+            let after = &rest[open + 1..];
+            let Some(close) = after.find(')') else {
+                break;
+            };
+            let tuple = &after[..close];
+            rest = &after[close + 1..];
+
+            let fields: Vec<&str> = tuple
+                .split(',')
+                .map(|f| f.trim().trim_matches('\'').trim())
+                .collect();
+
+            if fields.len() == 2 && fields[0] == "xkb" && !fields[1].is_empty() {
+                layouts.names.push(fields[1].to_string());
+            }
+        }
+
+        layouts
+    }
+}
+
+impl CompositorClient for GnomeClient {
+    fn get_keyboard_layouts(&self) -> anyhow::Result<KeyboardLayouts> {
+        let sources = self.read_sources()?;
+        Ok(self.parse_sources(&sources))
+    }
+
+    fn is_available(&self) -> bool {
+        if env::var("XDG_CURRENT_DESKTOP")
+            .map(|d| d.to_uppercase().contains("GNOME"))
+            .unwrap_or(false)
+        {
+            return true;
+        }
+
+        self.read_sources()
+            .map(|s| s.contains("('xkb'"))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> GnomeClient {
+        GnomeClient { flatpak: false }
+    }
+
+    #[test]
+    fn test_parse_sources_layout_and_variant() {
+        let layouts =
+            client().parse_sources("[('xkb', 'us+dvorak'), ('xkb', 'de')]");
+        assert_eq!(layouts.names, vec!["us+dvorak".to_string(), "de".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_sources_ignores_non_xkb() {
+        let layouts =
+            client().parse_sources("[('ibus', 'libpinyin'), ('xkb', 'fr')]");
+        assert_eq!(layouts.names, vec!["fr".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_sources_empty() {
+        let layouts = client().parse_sources("@a(ss) []");
+        assert!(layouts.is_empty());
+    }
+}
@@ -1,9 +1,11 @@
-use super::{CompositorClient, KeyboardLayouts};
+use super::{CompositorClient, KeyboardLayouts, LayoutEvent};
 use std::env;
-use std::io::{BufReader, Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::net::Shutdown;
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
 use std::time::Duration;
 
 #[derive(Debug)]
@@ -114,6 +116,18 @@ impl HyprlandClient {
     pub fn parse_layout_event(data: &str) -> Option<(&str, &str)> {
         data.split_once(',')
     }
+
+    #[must_use]
+    pub fn is_focus_event(event_name: &str) -> bool {
+        event_name == "focusedmon"
+    }
+
+    /// The `focusedmon` payload is `MONITOR,WORKSPACE`; the overlay only cares
+    /// about the monitor connector name.
+    #[must_use]
+    pub fn parse_focus_event(data: &str) -> &str {
+        data.split_once(',').map_or(data, |(monitor, _)| monitor)
+    }
 }
 
 impl CompositorClient for HyprlandClient {
@@ -125,6 +139,168 @@ impl CompositorClient for HyprlandClient {
     fn is_available(&self) -> bool {
         self.socket_path.exists()
     }
+
+    fn set_layout(&self, index: usize) -> anyhow::Result<()> {
+        let response = self.send_command(&format!("/switchxkblayout current {index}"))?;
+
+        // Hyprland answers `ok` on success, otherwise an error description.
+        if !response.trim().eq_ignore_ascii_case("ok") {
+            anyhow::bail!("Hyprland rejected layout switch: {}", response.trim());
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(&self) -> anyhow::Result<mpsc::Receiver<LayoutEvent>> {
+        let (tx, rx) = mpsc::channel();
+
+        // Initial snapshot so subscribers don't have to poll once up front.
+        if let Ok(layouts) = self.get_keyboard_layouts() {
+            let _ = tx.send(LayoutEvent::LayoutsChanged { layouts });
+        }
+
+        let reader = self.subscribe_events()?;
+
+        thread::spawn(move || {
+            let mut names: Vec<String> = Vec::new();
+
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                let Some((event_name, data)) = Self::parse_event(&line) else {
+                    continue;
+                };
+
+                if !Self::is_layout_event(event_name) {
+                    continue;
+                }
+
+                if let Some((_keyboard, layout_name)) = Self::parse_layout_event(data) {
+                    let index = names.iter().position(|n| n == layout_name).unwrap_or_else(|| {
+                        names.push(layout_name.to_string());
+                        names.len() - 1
+                    });
+
+                    if tx
+                        .send(LayoutEvent::LayoutSwitched {
+                            name: layout_name.to_string(),
+                            index,
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn watch_layout_events(&self) -> anyhow::Result<mpsc::Receiver<LayoutEvent>> {
+        let (tx, rx) = mpsc::channel();
+        let socket_path = self.socket_path.clone();
+        let event_socket_path = self.event_socket_path.clone();
+
+        thread::spawn(move || {
+            let mut backoff = Duration::from_millis(100);
+
+            loop {
+                let client = HyprlandClient {
+                    socket_path: socket_path.clone(),
+                    event_socket_path: event_socket_path.clone(),
+                };
+
+                // Resynchronize with a fresh snapshot on every (re)connect.
+                if let Ok(layouts) = client.get_keyboard_layouts() {
+                    if tx.send(LayoutEvent::LayoutsChanged { layouts }).is_err() {
+                        return;
+                    }
+                }
+
+                match client.subscribe_events() {
+                    Ok(reader) => {
+                        backoff = Duration::from_millis(100);
+                        let mut names: Vec<String> = Vec::new();
+
+                        for line in reader.lines() {
+                            let Ok(line) = line else {
+                                break;
+                            };
+
+                            let Some((event_name, data)) = Self::parse_event(&line) else {
+                                continue;
+                            };
+
+                            if !Self::is_layout_event(event_name) {
+                                continue;
+                            }
+
+                            if let Some((_keyboard, layout_name)) = Self::parse_layout_event(data) {
+                                let index =
+                                    names.iter().position(|n| n == layout_name).unwrap_or_else(
+                                        || {
+                                            names.push(layout_name.to_string());
+                                            names.len() - 1
+                                        },
+                                    );
+
+                                if tx
+                                    .send(LayoutEvent::LayoutSwitched {
+                                        name: layout_name.to_string(),
+                                        index,
+                                    })
+                                    .is_err()
+                                {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::debug!("Hyprland event subscription failed: {}", e);
+                    }
+                }
+
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(super::RECONNECT_MAX_BACKOFF);
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn subscribe_focus(&self) -> anyhow::Result<mpsc::Receiver<String>> {
+        let (tx, rx) = mpsc::channel();
+
+        let reader = self.subscribe_events()?;
+
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                let Some((event_name, data)) = Self::parse_event(&line) else {
+                    continue;
+                };
+
+                if !Self::is_focus_event(event_name) {
+                    continue;
+                }
+
+                let monitor = Self::parse_focus_event(data);
+
+                if tx.send(monitor.to_string()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
 }
 
 #[cfg(test)]
@@ -195,4 +371,16 @@ mod tests {
         assert!(HyprlandClient::is_layout_event("activelayout"));
         assert!(!HyprlandClient::is_layout_event("workspace"));
     }
+
+    #[test]
+    fn test_is_focus_event() {
+        assert!(HyprlandClient::is_focus_event("focusedmon"));
+        assert!(!HyprlandClient::is_focus_event("activelayout"));
+    }
+
+    #[test]
+    fn test_parse_focus_event() {
+        assert_eq!(HyprlandClient::parse_focus_event("DP-1,2"), "DP-1");
+        assert_eq!(HyprlandClient::parse_focus_event("HDMI-A-1"), "HDMI-A-1");
+    }
 }
@@ -0,0 +1,211 @@
+//! Opt-in inspector for the raw traffic exchanged with compositor IPC sockets.
+//!
+//! Enabled by setting the `KEYSTROKE_IPC_DEBUG` environment variable, it keeps
+//! the most recent messages in a ring buffer and mirrors each one to `tracing`,
+//! so a user filing a layout-detection bug can capture the exact bytes that went
+//! to and from Sway or niri. When the variable is unset every hook is a cheap
+//! no-op.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Environment variable that turns the inspector on.
+const ENV_FLAG: &str = "KEYSTROKE_IPC_DEBUG";
+
+/// Number of packets retained in the ring buffer.
+const RING_CAPACITY: usize = 256;
+
+/// i3-ipc frame header size, duplicated here so the decoder is self-contained.
+const IPC_HEADER_SIZE: usize = 14;
+
+/// Which way a recorded message was travelling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl Direction {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Sent => "->",
+            Self::Received => "<-",
+        }
+    }
+}
+
+/// A single captured message.
+#[derive(Debug, Clone)]
+pub struct Packet {
+    pub timestamp: SystemTime,
+
+    pub direction: Direction,
+
+    /// Human-readable message type (`GET_INPUTS`, `SUBSCRIBE`, or the verbatim
+    /// niri request), since the two transports type their messages differently.
+    pub message_type: String,
+
+    pub payload_len: usize,
+
+    /// Raw body, as sent or received.
+    pub body: String,
+}
+
+/// The decoded fields of an i3-ipc 14-byte frame header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub payload_len: u32,
+
+    pub message_type: u32,
+}
+
+/// Why a frame header failed to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// The six magic bytes weren't `i3-ipc`.
+    BadMagic([u8; 6]),
+
+    /// Fewer than [`IPC_HEADER_SIZE`] bytes were available.
+    Truncated { got: usize },
+}
+
+/// Decode an i3-ipc header into its `payload_len` and `message_type` after
+/// checking the `i3-ipc` magic, flagging a magic mismatch (with the bytes
+/// found) or a truncated header rather than silently erroring.
+pub fn decode_frame(header: &[u8]) -> Result<FrameHeader, FrameError> {
+    if header.len() < IPC_HEADER_SIZE {
+        return Err(FrameError::Truncated { got: header.len() });
+    }
+
+    if &header[0..6] != b"i3-ipc" {
+        let mut magic = [0u8; 6];
+        magic.copy_from_slice(&header[0..6]);
+        return Err(FrameError::BadMagic(magic));
+    }
+
+    let payload_len = u32::from_le_bytes(header[6..10].try_into().expect("slice is 4 bytes"));
+    let message_type = u32::from_le_bytes(header[10..14].try_into().expect("slice is 4 bytes"));
+
+    Ok(FrameHeader {
+        payload_len,
+        message_type,
+    })
+}
+
+/// Map a Sway i3-ipc message type number to its name, falling back to the raw
+/// number for types the overlay doesn't issue.
+#[must_use]
+pub fn sway_message_type(message_type: u32) -> String {
+    match message_type {
+        0 => "RUN_COMMAND".to_string(),
+        2 => "SUBSCRIBE".to_string(),
+        100 => "GET_INPUTS".to_string(),
+        other => format!("TYPE_{other}"),
+    }
+}
+
+/// Whether the inspector is active. Evaluated once on first use.
+#[must_use]
+pub fn is_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| std::env::var_os(ENV_FLAG).is_some())
+}
+
+fn ring() -> &'static Mutex<VecDeque<Packet>> {
+    static RING: OnceLock<Mutex<VecDeque<Packet>>> = OnceLock::new();
+    RING.get_or_init(|| Mutex::new(VecDeque::with_capacity(RING_CAPACITY)))
+}
+
+/// Record one message. A no-op unless the inspector is enabled.
+pub fn record(direction: Direction, message_type: impl Into<String>, body: &str) {
+    if !is_enabled() {
+        return;
+    }
+
+    let message_type = message_type.into();
+
+    tracing::debug!(
+        target: "keystroke::ipc",
+        "{} {} len={} {}",
+        direction.label(),
+        message_type,
+        body.len(),
+        body.trim()
+    );
+
+    let packet = Packet {
+        timestamp: SystemTime::now(),
+        direction,
+        message_type,
+        payload_len: body.len(),
+        body: body.to_string(),
+    };
+
+    if let Ok(mut ring) = ring().lock() {
+        if ring.len() == RING_CAPACITY {
+            ring.pop_front();
+        }
+        ring.push_back(packet);
+    }
+}
+
+/// Convenience wrapper for an outbound message.
+pub fn record_sent(message_type: impl Into<String>, body: &str) {
+    record(Direction::Sent, message_type, body);
+}
+
+/// Convenience wrapper for an inbound message.
+pub fn record_received(message_type: impl Into<String>, body: &str) {
+    record(Direction::Received, message_type, body);
+}
+
+/// A snapshot copy of the ring buffer, oldest first.
+#[must_use]
+pub fn snapshot() -> Vec<Packet> {
+    ring()
+        .lock()
+        .map(|ring| ring.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_frame_ok() {
+        let mut header = [0u8; IPC_HEADER_SIZE];
+        header[0..6].copy_from_slice(b"i3-ipc");
+        header[6..10].copy_from_slice(&7u32.to_le_bytes());
+        header[10..14].copy_from_slice(&100u32.to_le_bytes());
+
+        let decoded = decode_frame(&header).unwrap();
+        assert_eq!(decoded.payload_len, 7);
+        assert_eq!(decoded.message_type, 100);
+    }
+
+    #[test]
+    fn test_decode_frame_bad_magic() {
+        let mut header = [0u8; IPC_HEADER_SIZE];
+        header[0..6].copy_from_slice(b"xxxxxx");
+
+        assert!(matches!(decode_frame(&header), Err(FrameError::BadMagic(_))));
+    }
+
+    #[test]
+    fn test_decode_frame_truncated() {
+        let header = [0u8; 8];
+        assert!(matches!(
+            decode_frame(&header),
+            Err(FrameError::Truncated { got: 8 })
+        ));
+    }
+
+    #[test]
+    fn test_sway_message_type_names() {
+        assert_eq!(sway_message_type(100), "GET_INPUTS");
+        assert_eq!(sway_message_type(2), "SUBSCRIBE");
+        assert_eq!(sway_message_type(42), "TYPE_42");
+    }
+}
@@ -0,0 +1,356 @@
+//! A minimal, tolerant JSON reader shared by the Sway and niri clients.
+//!
+//! The compositor transports hand us well-formed JSON, but the fields we want
+//! (`xkb_layout_names` and its co-located `xkb_active_layout_index`, or niri's
+//! `names`/`current_idx`) are buried in responses whose exact shape varies
+//! between compositor versions. Rather than slice strings — which mispairs the
+//! layout names against the wrong device's active index on a multi-keyboard
+//! `GET_INPUTS` array, and silently drops `\uXXXX`-escaped names — we parse into
+//! a small owned value tree and query it structurally.
+
+/// A parsed JSON value. Objects preserve insertion order so callers can pick the
+/// first matching key the way the raw transports emit them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Look up a key on an object value, returning `None` for non-objects or
+    /// absent keys. Returns the first match on duplicate keys.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as usize),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the first JSON value in `input`; anything after it is ignored. Returns
+/// `None` on malformed input rather than erroring, matching the best-effort
+/// stance the string-slicing extractors took.
+#[must_use]
+pub fn parse(input: &str) -> Option<Json> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+
+    parser.skip_whitespace();
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    Some(value)
+}
+
+/// Depth-first search for the first value bound to `key` anywhere in the tree.
+/// Used where the legacy extractors matched the first textual occurrence of a
+/// key, regardless of nesting.
+#[must_use]
+pub fn find_first<'a>(value: &'a Json, key: &str) -> Option<&'a Json> {
+    match value {
+        Json::Object(pairs) => {
+            for (k, v) in pairs {
+                if k == key {
+                    return Some(v);
+                }
+            }
+            for (_, v) in pairs {
+                if let Some(found) = find_first(v, key) {
+                    return Some(found);
+                }
+            }
+            None
+        }
+        Json::Array(items) => items.iter().find_map(|item| find_first(item, key)),
+        _ => None,
+    }
+}
+
+/// Select the keyboard input object from a `GET_INPUTS`-style value — either a
+/// top-level array of input objects or a single object. The chosen object is the
+/// first carrying any of the xkb layout fields, so a later read of
+/// `xkb_active_layout_index` is guaranteed to come from the same device as the
+/// `xkb_layout_names` it pairs with.
+#[must_use]
+pub fn find_keyboard_object(value: &Json) -> Option<&Json> {
+    fn is_keyboard(value: &Json) -> bool {
+        value.get("xkb_layout_names").is_some()
+            || value.get("xkb_active_layout_index").is_some()
+            || value.get("xkb_active_layout_name").is_some()
+    }
+
+    match value {
+        Json::Array(items) => items.iter().find(|item| is_keyboard(item)),
+        obj @ Json::Object(_) if is_keyboard(obj) => Some(obj),
+        _ => None,
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.get(self.pos).copied();
+        if ch.is_some() {
+            self.pos += 1;
+        }
+        ch
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Option<Json> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => self.parse_object(),
+            '[' => self.parse_array(),
+            '"' => self.parse_string().map(Json::String),
+            't' | 'f' => self.parse_bool(),
+            'n' => self.parse_null(),
+            _ => self.parse_number(),
+        }
+    }
+
+    fn parse_object(&mut self) -> Option<Json> {
+        self.next(); // consume '{'
+        let mut pairs = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Some(Json::Object(pairs));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+
+            self.skip_whitespace();
+            if self.next()? != ':' {
+                return None;
+            }
+
+            let value = self.parse_value()?;
+            pairs.push((key, value));
+
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                '}' => return Some(Json::Object(pairs)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Option<Json> {
+        self.next(); // consume '['
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Some(Json::Array(items));
+        }
+
+        loop {
+            let value = self.parse_value()?;
+            items.push(value);
+
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                ']' => return Some(Json::Array(items)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        if self.next()? != '"' {
+            return None;
+        }
+
+        let mut out = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(out),
+                '\\' => match self.next()? {
+                    '"' => out.push('"'),
+                    '\\' => out.push('\\'),
+                    '/' => out.push('/'),
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    'r' => out.push('\r'),
+                    'b' => out.push('\u{0008}'),
+                    'f' => out.push('\u{000C}'),
+                    'u' => out.push(self.parse_unicode_escape()?),
+                    _ => return None,
+                },
+                ch => out.push(ch),
+            }
+        }
+    }
+
+    fn parse_unicode_escape(&mut self) -> Option<char> {
+        let mut code = 0u32;
+        for _ in 0..4 {
+            code = code * 16 + self.next()?.to_digit(16)?;
+        }
+        char::from_u32(code)
+    }
+
+    fn parse_bool(&mut self) -> Option<Json> {
+        if self.consume_literal("true") {
+            Some(Json::Bool(true))
+        } else if self.consume_literal("false") {
+            Some(Json::Bool(false))
+        } else {
+            None
+        }
+    }
+
+    fn parse_null(&mut self) -> Option<Json> {
+        self.consume_literal("null").then_some(Json::Null)
+    }
+
+    fn consume_literal(&mut self, literal: &str) -> bool {
+        let end = self.pos + literal.len();
+        if end <= self.chars.len() && self.chars[self.pos..end].iter().copied().eq(literal.chars())
+        {
+            self.pos = end;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<Json> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some('0'..='9' | '-' | '+' | '.' | 'e' | 'E')
+        ) {
+            self.pos += 1;
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse::<f64>().ok().map(Json::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_object_and_lookup() {
+        let value = parse(r#"{"a": 1, "b": "two"}"#).unwrap();
+        assert_eq!(value.get("a").and_then(Json::as_usize), Some(1));
+        assert_eq!(value.get("b").and_then(Json::as_str), Some("two"));
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let value = parse(r#""a\"b\\c\/d\ne""#).unwrap();
+        assert_eq!(value.as_str(), Some("a\"b\\c/d\ne"));
+    }
+
+    #[test]
+    fn test_unicode_escape() {
+        // "Français" written with a \u escape for the cedilla, not a literal UTF-8 byte.
+        let value = parse(r#""Fran\u00e7ais""#).unwrap();
+        assert_eq!(value.as_str(), Some("Français"));
+    }
+
+    #[test]
+    fn test_find_keyboard_object_skips_non_keyboard() {
+        let value = parse(
+            r#"[
+                {"type": "pointer", "name": "mouse"},
+                {"type": "keyboard", "xkb_layout_names": ["US"], "xkb_active_layout_index": 0}
+            ]"#,
+        )
+        .unwrap();
+
+        let keyboard = find_keyboard_object(&value).unwrap();
+        assert_eq!(
+            keyboard.get("xkb_layout_names").and_then(Json::as_array).map(<[_]>::len),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_two_keyboards_pair_within_object() {
+        // Two keyboards with different active indices; we must read the index
+        // from the same object we took the names from, not the other device's.
+        let value = parse(
+            r#"[
+                {"type": "keyboard", "xkb_layout_names": ["English", "German"], "xkb_active_layout_index": 1},
+                {"type": "keyboard", "xkb_layout_names": ["French"], "xkb_active_layout_index": 0}
+            ]"#,
+        )
+        .unwrap();
+
+        let keyboard = find_keyboard_object(&value).unwrap();
+        let names = keyboard.get("xkb_layout_names").and_then(Json::as_array).unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(
+            keyboard.get("xkb_active_layout_index").and_then(Json::as_usize),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_find_first_by_key() {
+        let value = parse(r#"{"outer": {"names": ["a", "b"], "current_idx": 1}}"#).unwrap();
+        assert_eq!(
+            find_first(&value, "current_idx").and_then(Json::as_usize),
+            Some(1)
+        );
+        assert_eq!(
+            find_first(&value, "names").and_then(Json::as_array).map(<[_]>::len),
+            Some(2)
+        );
+    }
+}
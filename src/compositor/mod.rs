@@ -1,9 +1,18 @@
+pub mod gnome;
 pub mod hyprland;
+pub mod ipc_debug;
+pub mod json;
+pub mod mutter;
 pub mod niri;
 pub mod sway;
 
 use std::env;
 use std::fmt;
+use std::time::Duration;
+
+/// Upper bound for the exponential reconnect backoff used by the unified layout
+/// event watcher, starting from 100ms and doubling up to this ceiling.
+pub(crate) const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Compositor {
@@ -13,6 +22,8 @@ pub enum Compositor {
 
     Niri,
 
+    Gnome,
+
     River,
 
     Dwl,
@@ -27,12 +38,17 @@ pub enum Compositor {
 impl Compositor {
     #[must_use]
     pub const fn supports_layout_query(&self) -> bool {
-        matches!(self, Self::Hyprland | Self::Sway | Self::Niri)
+        matches!(self, Self::Hyprland | Self::Sway | Self::Niri | Self::Gnome)
     }
 
     #[must_use]
     pub const fn supports_layout_events(&self) -> bool {
-        matches!(self, Self::Hyprland | Self::Sway | Self::Niri)
+        matches!(self, Self::Hyprland | Self::Sway | Self::Niri | Self::Gnome)
+    }
+
+    #[must_use]
+    pub const fn supports_focus_events(&self) -> bool {
+        matches!(self, Self::Hyprland | Self::Sway)
     }
 
     #[must_use]
@@ -53,6 +69,7 @@ impl fmt::Display for Compositor {
             Self::Hyprland => write!(f, "Hyprland"),
             Self::Sway => write!(f, "Sway"),
             Self::Niri => write!(f, "Niri"),
+            Self::Gnome => write!(f, "GNOME"),
             Self::River => write!(f, "River"),
             Self::Dwl => write!(f, "dwl"),
             Self::Labwc => write!(f, "Labwc"),
@@ -105,6 +122,40 @@ pub trait CompositorClient: Send + Sync {
     fn get_keyboard_layouts(&self) -> anyhow::Result<KeyboardLayouts>;
 
     fn is_available(&self) -> bool;
+
+    /// Switch the active keyboard layout to the given zero-based index. Backends
+    /// that can't drive a switch (read-only transports) return an error.
+    fn set_layout(&self, index: usize) -> anyhow::Result<()> {
+        let _ = index;
+        anyhow::bail!("layout switching not supported by this backend")
+    }
+
+    /// Subscribe to live layout-change events. The returned receiver yields an
+    /// initial [`LayoutEvent::LayoutsChanged`] snapshot on connect followed by a
+    /// [`LayoutEvent::LayoutSwitched`] for each change. Backends that cannot
+    /// stream events (e.g. one-shot `dconf` reads) return an error.
+    fn subscribe(&self) -> anyhow::Result<std::sync::mpsc::Receiver<LayoutEvent>> {
+        anyhow::bail!("layout event subscription not supported by this backend")
+    }
+
+    /// Spawn a background thread that streams a single normalized sequence of
+    /// [`LayoutEvent`]s, reconnecting with exponential backoff on EOF or error
+    /// and re-emitting a fresh [`LayoutEvent::LayoutsChanged`] snapshot after
+    /// each (re)connect so consumers resynchronize. Unlike [`Self::subscribe`],
+    /// callers never have to poll or frame the raw transport themselves.
+    /// Backends without an event stream return an error.
+    fn watch_layout_events(&self) -> anyhow::Result<std::sync::mpsc::Receiver<LayoutEvent>> {
+        anyhow::bail!("layout event watching not supported by this backend")
+    }
+
+    /// Subscribe to focused-output changes. The returned receiver yields the
+    /// connector name (e.g. `DP-1`) of the newly focused monitor for each
+    /// change, driven from a background thread over the same event socket as
+    /// [`CompositorClient::subscribe`]. Backends without an event stream return
+    /// an error.
+    fn subscribe_focus(&self) -> anyhow::Result<std::sync::mpsc::Receiver<String>> {
+        anyhow::bail!("focus event subscription not supported by this backend")
+    }
 }
 
 #[must_use]
@@ -147,6 +198,9 @@ pub fn detect() -> Compositor {
         if desktop_lower.contains("niri") {
             return Compositor::Niri;
         }
+        if desktop_lower.contains("gnome") {
+            return Compositor::Gnome;
+        }
     }
 
     Compositor::Unknown
@@ -164,6 +218,11 @@ pub fn create_client(compositor: Compositor) -> Option<Box<dyn CompositorClient>
         Compositor::Niri => {
             niri::NiriClient::new().map(|c| Box::new(c) as Box<dyn CompositorClient>)
         }
+        Compositor::Gnome => mutter::MutterClient::new()
+            .map(|c| Box::new(c) as Box<dyn CompositorClient>)
+            .or_else(|| {
+                gnome::GnomeClient::new().map(|c| Box::new(c) as Box<dyn CompositorClient>)
+            }),
         _ => None,
     }
 }
@@ -0,0 +1,98 @@
+use super::{CompositorClient, KeyboardLayouts};
+use anyhow::Context;
+use gtk4::gio;
+use gtk4::prelude::*;
+use std::env;
+
+/// GSettings schema holding GNOME's ordered list of keyboard input sources.
+pub const SCHEMA_ID: &str = "org.gnome.desktop.input-sources";
+
+/// Layout backend for GNOME / Mutter Wayland sessions. Unlike the one-shot
+/// [`GnomeClient`](super::gnome::GnomeClient) `dconf` reader this talks to
+/// GSettings directly, so it can report the active source `current` index and
+/// stream live changes off the `changed::current` / `changed::sources` signals
+/// (driven from [`LayoutManager`](crate::input::layout::LayoutManager)).
+#[derive(Debug)]
+pub struct MutterClient {
+    _private: (),
+}
+
+impl MutterClient {
+    /// Construct a client, returning `None` when the `input-sources` schema is
+    /// not installed, or when the session isn't GNOME and no `xkb` sources are
+    /// configured.
+    #[must_use]
+    pub fn new() -> Option<Self> {
+        if Self::open_settings().is_none() {
+            tracing::debug!("GNOME input-sources schema not installed");
+            return None;
+        }
+
+        let client = Self { _private: () };
+        if client.is_available() {
+            Some(client)
+        } else {
+            None
+        }
+    }
+
+    /// Open the `input-sources` [`gio::Settings`], or `None` when the schema is
+    /// not part of the installed set (mirrors the UI's settings lookup).
+    pub fn open_settings() -> Option<gio::Settings> {
+        let source = gio::SettingsSchemaSource::default()?;
+        source.lookup(SCHEMA_ID, true)?;
+        Some(gio::Settings::new(SCHEMA_ID))
+    }
+
+    /// Read the current `sources`/`current` pair into a [`KeyboardLayouts`].
+    /// Only `xkb` sources are kept; their ids (`us+dvorak`, `de`, …) pass
+    /// through verbatim for the XKB machinery to split.
+    pub fn read_layouts(settings: &gio::Settings) -> KeyboardLayouts {
+        let mut layouts = KeyboardLayouts::default();
+
+        let sources = settings.value("sources");
+        for i in 0..sources.n_children() {
+            let entry = sources.child_value(i);
+            if entry.n_children() < 2 {
+                continue;
+            }
+            let kind = entry.child_value(0);
+            let id = entry.child_value(1);
+            if kind.str() == Some("xkb") {
+                if let Some(id) = id.str() {
+                    if !id.is_empty() {
+                        layouts.names.push(id.to_string());
+                    }
+                }
+            }
+        }
+
+        // `current` indexes the full source list; clamp it to the xkb subset so
+        // an active ibus source doesn't point past the end.
+        let current = settings.uint("current") as usize;
+        layouts.current_idx = current.min(layouts.names.len().saturating_sub(1));
+
+        layouts
+    }
+}
+
+impl CompositorClient for MutterClient {
+    fn get_keyboard_layouts(&self) -> anyhow::Result<KeyboardLayouts> {
+        let settings =
+            Self::open_settings().context("GNOME input-sources schema not installed")?;
+        Ok(Self::read_layouts(&settings))
+    }
+
+    fn is_available(&self) -> bool {
+        if env::var("XDG_CURRENT_DESKTOP")
+            .map(|d| d.to_uppercase().contains("GNOME"))
+            .unwrap_or(false)
+        {
+            return true;
+        }
+
+        Self::open_settings()
+            .map(|s| !Self::read_layouts(&s).is_empty())
+            .unwrap_or(false)
+    }
+}
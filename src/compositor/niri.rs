@@ -1,3 +1,5 @@
+use super::ipc_debug;
+use super::json::{self, Json};
 use super::{CompositorClient, KeyboardLayouts, LayoutEvent};
 use std::env;
 use std::io::{BufRead, BufReader, Write};
@@ -32,10 +34,14 @@ impl NiriClient {
         writeln!(stream, "{}", request)?;
         stream.flush()?;
 
+        ipc_debug::record_sent(request, request);
+
         let mut reader = BufReader::new(stream);
         let mut response = String::new();
         reader.read_line(&mut response)?;
 
+        ipc_debug::record_received(request, &response);
+
         Ok(response)
     }
 
@@ -54,48 +60,14 @@ impl NiriClient {
     }
 
     fn extract_names_array(&self, json: &str) -> Option<Vec<String>> {
-        let key = "\"names\"";
-        let key_pos = json.find(key)?;
-        let after_key = &json[key_pos + key.len()..];
-
-        let bracket_start = after_key.find('[')?;
-        let array_content_start = &after_key[bracket_start + 1..];
-
-        let bracket_end = array_content_start.find(']')?;
-        let array_content = &array_content_start[..bracket_end];
-
-        let mut names = Vec::new();
-        let mut in_string = false;
-        let mut escape_next = false;
-        let mut current = String::new();
-
-        for ch in array_content.chars() {
-            if escape_next {
-                current.push(ch);
-                escape_next = false;
-                continue;
-            }
-
-            match ch {
-                '\\' if in_string => {
-                    escape_next = true;
-                }
-                '"' if !in_string => {
-                    in_string = true;
-                    current.clear();
-                }
-                '"' if in_string => {
-                    in_string = false;
-                    if !current.is_empty() {
-                        names.push(current.clone());
-                    }
-                }
-                _ if in_string => {
-                    current.push(ch);
-                }
-                _ => {}
-            }
-        }
+        let value = json::parse(json)?;
+        let names: Vec<String> = json::find_first(&value, "names")?
+            .as_array()?
+            .iter()
+            .filter_map(Json::as_str)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
 
         if names.is_empty() {
             None
@@ -105,20 +77,8 @@ impl NiriClient {
     }
 
     fn extract_current_idx(&self, json: &str) -> Option<usize> {
-        let key = "\"current_idx\"";
-        let key_pos = json.find(key)?;
-        let after_key = &json[key_pos + key.len()..];
-
-        let colon_pos = after_key.find(':')?;
-        let after_colon = &after_key[colon_pos + 1..];
-
-        let num_str: String = after_colon
-            .chars()
-            .skip_while(|c| c.is_whitespace())
-            .take_while(|c| c.is_ascii_digit())
-            .collect();
-
-        num_str.parse().ok()
+        let value = json::parse(json)?;
+        json::find_first(&value, "current_idx").and_then(Json::as_usize)
     }
 
     pub fn subscribe_events(&self) -> anyhow::Result<BufReader<UnixStream>> {
@@ -127,11 +87,15 @@ impl NiriClient {
         writeln!(stream, r#""EventStream""#)?;
         stream.flush()?;
 
+        ipc_debug::record_sent("EventStream", r#""EventStream""#);
+
         let mut reader = BufReader::new(stream);
 
         let mut ack = String::new();
         reader.read_line(&mut ack)?;
 
+        ipc_debug::record_received("EventStream", &ack);
+
         if !ack.contains("\"Ok\"") && !ack.contains("\"Handled\"") {
             anyhow::bail!("Failed to subscribe to Niri events: {}", ack.trim());
         }
@@ -161,20 +125,8 @@ impl NiriClient {
     }
 
     fn extract_event_layout_index(&self, json: &str) -> Option<usize> {
-        let key = "\"idx\"";
-        let key_pos = json.find(key)?;
-        let after_key = &json[key_pos + key.len()..];
-
-        let colon_pos = after_key.find(':')?;
-        let after_colon = &after_key[colon_pos + 1..];
-
-        let num_str: String = after_colon
-            .chars()
-            .skip_while(|c| c.is_whitespace())
-            .take_while(|c| c.is_ascii_digit())
-            .collect();
-
-        num_str.parse().ok()
+        let value = json::parse(json)?;
+        json::find_first(&value, "idx").and_then(Json::as_usize)
     }
 }
 
@@ -187,6 +139,96 @@ impl CompositorClient for NiriClient {
     fn is_available(&self) -> bool {
         std::path::Path::new(&self.socket_path).exists()
     }
+
+    fn set_layout(&self, index: usize) -> anyhow::Result<()> {
+        let request = format!(
+            r#"{{"Action":{{"SwitchLayout":{{"layout":{{"Index":{index}}}}}}}}}"#
+        );
+        let response = self.send_request(&request)?;
+
+        if response.contains("\"Err\"") {
+            anyhow::bail!("niri rejected layout switch: {}", response.trim());
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(&self) -> anyhow::Result<std::sync::mpsc::Receiver<LayoutEvent>> {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        if let Ok(layouts) = self.get_keyboard_layouts() {
+            let _ = tx.send(LayoutEvent::LayoutsChanged { layouts });
+        }
+
+        let reader = self.subscribe_events()?;
+        let helper = NiriClient {
+            socket_path: self.socket_path.clone(),
+        };
+
+        std::thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                if let Some(event) = helper.parse_event(&line) {
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn watch_layout_events(&self) -> anyhow::Result<std::sync::mpsc::Receiver<LayoutEvent>> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let socket_path = self.socket_path.clone();
+
+        std::thread::spawn(move || {
+            let mut backoff = Duration::from_millis(100);
+
+            loop {
+                let client = NiriClient {
+                    socket_path: socket_path.clone(),
+                };
+
+                // Resynchronize the consumer on every (re)connect.
+                if let Ok(layouts) = client.get_keyboard_layouts() {
+                    if tx.send(LayoutEvent::LayoutsChanged { layouts }).is_err() {
+                        return;
+                    }
+                }
+
+                match client.subscribe_events() {
+                    Ok(reader) => {
+                        backoff = Duration::from_millis(100);
+
+                        for line in reader.lines() {
+                            let Ok(line) = line else {
+                                break;
+                            };
+
+                            if let Some(event) = client.parse_event(&line) {
+                                if tx.send(event).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::debug!("Niri event subscription failed: {}", e);
+                    }
+                }
+
+                std::thread::sleep(backoff);
+                backoff = (backoff * 2).min(super::RECONNECT_MAX_BACKOFF);
+            }
+        });
+
+        Ok(rx)
+    }
 }
 
 #[cfg(test)]
@@ -1,13 +1,19 @@
-use super::{CompositorClient, KeyboardLayouts};
+use super::ipc_debug;
+use super::json::{self, Json};
+use super::{CompositorClient, KeyboardLayouts, LayoutEvent};
 use std::env;
 use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
+use std::sync::mpsc;
+use std::thread;
 use std::time::Duration;
 
 const IPC_MAGIC: &[u8; 6] = b"i3-ipc";
 
 const IPC_HEADER_SIZE: usize = 14;
 
+const IPC_RUN_COMMAND: u32 = 0;
+
 const IPC_GET_INPUTS: u32 = 100;
 
 #[allow(dead_code)]
@@ -55,106 +61,118 @@ impl SwayClient {
             stream.write_all(payload)?;
         }
 
+        ipc_debug::record_sent(
+            ipc_debug::sway_message_type(message_type),
+            &String::from_utf8_lossy(payload),
+        );
+
         let mut resp_header = [0u8; IPC_HEADER_SIZE];
         stream.read_exact(&mut resp_header)?;
 
-        if &resp_header[0..6] != IPC_MAGIC {
-            anyhow::bail!("Invalid i3-IPC response: magic mismatch");
-        }
-
-        let payload_len = u32::from_le_bytes(resp_header[6..10].try_into()?);
+        let frame = match ipc_debug::decode_frame(&resp_header) {
+            Ok(frame) => frame,
+            Err(e) => anyhow::bail!("Invalid i3-IPC response: {:?}", e),
+        };
 
-        let mut payload = vec![0u8; payload_len as usize];
+        let mut payload = vec![0u8; frame.payload_len as usize];
         stream.read_exact(&mut payload)?;
 
-        String::from_utf8(payload).map_err(Into::into)
+        let response = String::from_utf8(payload)?;
+        ipc_debug::record_received(ipc_debug::sway_message_type(frame.message_type), &response);
+
+        Ok(response)
     }
 
     fn parse_inputs_response(&self, json: &str) -> KeyboardLayouts {
         let mut layouts = KeyboardLayouts::default();
-        let mut seen_layouts = std::collections::HashSet::new();
 
-        if let Some(layouts_array) = self.extract_layout_names_array(json) {
-            for name in layouts_array {
-                if !name.is_empty() && seen_layouts.insert(name.clone()) {
-                    layouts.names.push(name);
+        let Some(value) = json::parse(json) else {
+            return layouts;
+        };
+        let Some(keyboard) = json::find_keyboard_object(&value) else {
+            return layouts;
+        };
+
+        let mut seen_layouts = std::collections::HashSet::new();
+        if let Some(names) = keyboard.get("xkb_layout_names").and_then(Json::as_array) {
+            for name in names.iter().filter_map(Json::as_str) {
+                if !name.is_empty() && seen_layouts.insert(name.to_string()) {
+                    layouts.names.push(name.to_string());
                 }
             }
         }
 
-        if let Some(idx) = self.extract_active_layout_index(json) {
+        if let Some(idx) = keyboard
+            .get("xkb_active_layout_index")
+            .and_then(Json::as_usize)
+        {
             layouts.current_idx = idx;
         }
 
         if layouts.names.is_empty() {
-            if let Some(name) = self.extract_active_layout_name(json) {
-                layouts.names.push(name);
+            if let Some(name) = keyboard
+                .get("xkb_active_layout_name")
+                .and_then(Json::as_str)
+                .filter(|name| !name.is_empty())
+            {
+                layouts.names.push(name.to_string());
             }
         }
 
         layouts
     }
 
-    fn extract_layout_names_array(&self, json: &str) -> Option<Vec<String>> {
-        let key = "\"xkb_layout_names\"";
-        let key_pos = json.find(key)?;
-        let after_key = &json[key_pos + key.len()..];
+    fn extract_active_layout_name(&self, json: &str) -> Option<String> {
+        let value = json::parse(json)?;
+        json::find_first(&value, "xkb_active_layout_name")
+            .and_then(Json::as_str)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    }
 
-        let bracket_pos = after_key.find('[')?;
-        let array_start = &after_key[bracket_pos + 1..];
+    #[allow(dead_code)]
+    fn extract_active_layout_index(&self, json: &str) -> Option<usize> {
+        let value = json::parse(json)?;
+        json::find_first(&value, "xkb_active_layout_index").and_then(Json::as_usize)
+    }
 
-        let bracket_end = array_start.find(']')?;
-        let array_content = &array_start[..bracket_end];
+    #[allow(dead_code)]
+    pub fn subscribe_events(&self) -> anyhow::Result<UnixStream> {
+        self.subscribe_to(br#"["input"]"#)
+    }
 
-        let mut names = Vec::new();
-        let mut in_string = false;
-        let mut current = String::new();
+    fn subscribe_to(&self, payload: &[u8]) -> anyhow::Result<UnixStream> {
+        let mut stream = UnixStream::connect(&self.socket_path)?;
 
-        for ch in array_content.chars() {
-            match ch {
-                '"' if !in_string => {
-                    in_string = true;
-                    current.clear();
-                }
-                '"' if in_string => {
-                    in_string = false;
-                    if !current.is_empty() {
-                        names.push(current.clone());
-                    }
-                }
-                _ if in_string => {
-                    current.push(ch);
-                }
-                _ => {}
-            }
-        }
+        let header = Self::build_header(payload.len() as u32, IPC_SUBSCRIBE);
 
-        if names.is_empty() {
-            None
-        } else {
-            Some(names)
-        }
-    }
+        stream.write_all(&header)?;
+        stream.write_all(payload)?;
 
-    fn extract_active_layout_index(&self, json: &str) -> Option<usize> {
-        let key = "\"xkb_active_layout_index\"";
-        let key_pos = json.find(key)?;
-        let after_key = &json[key_pos + key.len()..];
+        ipc_debug::record_sent(
+            ipc_debug::sway_message_type(IPC_SUBSCRIBE),
+            &String::from_utf8_lossy(payload),
+        );
 
-        let colon_pos = after_key.find(':')?;
-        let after_colon = &after_key[colon_pos + 1..];
+        let mut resp_header = [0u8; IPC_HEADER_SIZE];
+        stream.read_exact(&mut resp_header)?;
 
-        let num_str: String = after_colon
-            .chars()
-            .skip_while(|c| c.is_whitespace())
-            .take_while(|c| c.is_ascii_digit())
-            .collect();
+        let payload_len = u32::from_le_bytes(resp_header[6..10].try_into()?);
+        let mut response = vec![0u8; payload_len as usize];
+        stream.read_exact(&mut response)?;
+
+        ipc_debug::record_received(
+            ipc_debug::sway_message_type(IPC_SUBSCRIBE),
+            &String::from_utf8_lossy(&response),
+        );
 
-        num_str.parse().ok()
+        Ok(stream)
     }
 
-    fn extract_active_layout_name(&self, json: &str) -> Option<String> {
-        let key = "\"xkb_active_layout_name\"";
+    /// Pull the connector of the newly focused output out of a `workspace`
+    /// focus event (`"current": { ... "output": "HDMI-A-1" ... }`).
+    fn extract_focused_output(&self, json: &str) -> Option<String> {
+        let key = "\"output\"";
         let key_pos = json.find(key)?;
         let after_key = &json[key_pos + key.len()..];
 
@@ -173,26 +191,6 @@ impl SwayClient {
             Some(name.to_string())
         }
     }
-
-    #[allow(dead_code)]
-    pub fn subscribe_events(&self) -> anyhow::Result<UnixStream> {
-        let mut stream = UnixStream::connect(&self.socket_path)?;
-
-        let payload = br#"["input"]"#;
-        let header = Self::build_header(payload.len() as u32, IPC_SUBSCRIBE);
-
-        stream.write_all(&header)?;
-        stream.write_all(payload)?;
-
-        let mut resp_header = [0u8; IPC_HEADER_SIZE];
-        stream.read_exact(&mut resp_header)?;
-
-        let payload_len = u32::from_le_bytes(resp_header[6..10].try_into()?);
-        let mut _response = vec![0u8; payload_len as usize];
-        stream.read_exact(&mut _response)?;
-
-        Ok(stream)
-    }
 }
 
 impl CompositorClient for SwayClient {
@@ -204,6 +202,214 @@ impl CompositorClient for SwayClient {
     fn is_available(&self) -> bool {
         std::path::Path::new(&self.socket_path).exists()
     }
+
+    fn set_layout(&self, index: usize) -> anyhow::Result<()> {
+        let command = format!("input * xkb_switch_layout {index}");
+        let response = self.send_message(IPC_RUN_COMMAND, command.as_bytes())?;
+
+        // Sway replies with a JSON array of `{"success": bool}` objects.
+        if response.contains("\"success\": false") || response.contains("\"success\":false") {
+            anyhow::bail!("Sway rejected layout switch: {}", response.trim());
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(&self) -> anyhow::Result<mpsc::Receiver<LayoutEvent>> {
+        let (tx, rx) = mpsc::channel();
+
+        let initial = self.get_keyboard_layouts().unwrap_or_default();
+        let mut names = initial.names.clone();
+        let _ = tx.send(LayoutEvent::LayoutsChanged { layouts: initial });
+
+        let mut stream = self.subscribe_events()?;
+        let helper = SwayClient {
+            socket_path: self.socket_path.clone(),
+        };
+
+        thread::spawn(move || {
+            let mut last: Option<String> = None;
+
+            loop {
+                let mut header = [0u8; IPC_HEADER_SIZE];
+                if stream.read_exact(&mut header).is_err() {
+                    break;
+                }
+                if &header[0..6] != b"i3-ipc" {
+                    continue;
+                }
+
+                let Ok(len_bytes) = header[6..10].try_into() else {
+                    continue;
+                };
+                let payload_len = u32::from_le_bytes(len_bytes) as usize;
+
+                let mut payload = vec![0u8; payload_len];
+                if stream.read_exact(&mut payload).is_err() {
+                    break;
+                }
+
+                let Ok(json) = String::from_utf8(payload) else {
+                    continue;
+                };
+
+                if let Some(name) = helper.extract_active_layout_name(&json) {
+                    if last.as_deref() == Some(name.as_str()) {
+                        continue;
+                    }
+                    last = Some(name.clone());
+
+                    let index = names.iter().position(|n| *n == name).unwrap_or_else(|| {
+                        names.push(name.clone());
+                        names.len() - 1
+                    });
+
+                    if tx
+                        .send(LayoutEvent::LayoutSwitched { name, index })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn watch_layout_events(&self) -> anyhow::Result<mpsc::Receiver<LayoutEvent>> {
+        let (tx, rx) = mpsc::channel();
+        let socket_path = self.socket_path.clone();
+
+        thread::spawn(move || {
+            let mut backoff = Duration::from_millis(100);
+
+            loop {
+                let client = SwayClient {
+                    socket_path: socket_path.clone(),
+                };
+
+                // Resynchronize with a fresh snapshot on every (re)connect.
+                let initial = client.get_keyboard_layouts().unwrap_or_default();
+                let mut names = initial.names.clone();
+                if tx
+                    .send(LayoutEvent::LayoutsChanged { layouts: initial })
+                    .is_err()
+                {
+                    return;
+                }
+
+                match client.subscribe_events() {
+                    Ok(mut stream) => {
+                        backoff = Duration::from_millis(100);
+                        let mut last: Option<String> = None;
+
+                        loop {
+                            let mut header = [0u8; IPC_HEADER_SIZE];
+                            if stream.read_exact(&mut header).is_err() {
+                                break;
+                            }
+                            if &header[0..6] != IPC_MAGIC {
+                                continue;
+                            }
+
+                            let Ok(len_bytes) = header[6..10].try_into() else {
+                                continue;
+                            };
+                            let payload_len = u32::from_le_bytes(len_bytes) as usize;
+
+                            let mut payload = vec![0u8; payload_len];
+                            if stream.read_exact(&mut payload).is_err() {
+                                break;
+                            }
+
+                            let Ok(json) = String::from_utf8(payload) else {
+                                continue;
+                            };
+
+                            if let Some(name) = client.extract_active_layout_name(&json) {
+                                if last.as_deref() == Some(name.as_str()) {
+                                    continue;
+                                }
+                                last = Some(name.clone());
+
+                                let index =
+                                    names.iter().position(|n| *n == name).unwrap_or_else(|| {
+                                        names.push(name.clone());
+                                        names.len() - 1
+                                    });
+
+                                if tx
+                                    .send(LayoutEvent::LayoutSwitched { name, index })
+                                    .is_err()
+                                {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::debug!("Sway event subscription failed: {}", e);
+                    }
+                }
+
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(super::RECONNECT_MAX_BACKOFF);
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn subscribe_focus(&self) -> anyhow::Result<mpsc::Receiver<String>> {
+        let (tx, rx) = mpsc::channel();
+
+        let mut stream = self.subscribe_to(br#"["workspace"]"#)?;
+        let helper = SwayClient {
+            socket_path: self.socket_path.clone(),
+        };
+
+        thread::spawn(move || {
+            let mut last: Option<String> = None;
+
+            loop {
+                let mut header = [0u8; IPC_HEADER_SIZE];
+                if stream.read_exact(&mut header).is_err() {
+                    break;
+                }
+                if &header[0..6] != IPC_MAGIC {
+                    continue;
+                }
+
+                let Ok(len_bytes) = header[6..10].try_into() else {
+                    continue;
+                };
+                let payload_len = u32::from_le_bytes(len_bytes) as usize;
+
+                let mut payload = vec![0u8; payload_len];
+                if stream.read_exact(&mut payload).is_err() {
+                    break;
+                }
+
+                let Ok(json) = String::from_utf8(payload) else {
+                    continue;
+                };
+
+                if let Some(output) = helper.extract_focused_output(&json) {
+                    if last.as_deref() == Some(output.as_str()) {
+                        continue;
+                    }
+                    last = Some(output.clone());
+
+                    if tx.send(output).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
 }
 
 #[cfg(test)]
@@ -266,18 +472,57 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_layout_names_array() {
+    fn test_parse_inputs_two_keyboards() {
         let client = create_test_client();
 
-        let json = r#"{"xkb_layout_names": ["English", "Deutsch", "Francais"]}"#;
-        let names = client.extract_layout_names_array(json);
+        // A pointer plus two keyboards with different active indices: the names
+        // and the active index must come from the same (first keyboard) object.
+        let json = r#"[
+            {"type": "pointer", "name": "Logitech Mouse"},
+            {
+                "type": "keyboard",
+                "xkb_layout_names": ["English (US)", "German"],
+                "xkb_active_layout_index": 1,
+                "xkb_active_layout_name": "German"
+            },
+            {
+                "type": "keyboard",
+                "xkb_layout_names": ["French"],
+                "xkb_active_layout_index": 0
+            }
+        ]"#;
+
+        let layouts = client.parse_inputs_response(json);
+        assert_eq!(layouts.names, vec!["English (US)", "German"]);
+        assert_eq!(layouts.current_idx, 1);
+        assert_eq!(layouts.current_name(), Some("German"));
+    }
+
+    #[test]
+    fn test_parse_inputs_unicode_escape() {
+        let client = create_test_client();
+
+        // "Français" delivered with a \u-escaped cedilla (not a literal UTF-8 byte).
+        let json = r#"[
+            {
+                "type": "keyboard",
+                "xkb_layout_names": ["English (US)", "Fran\u00e7ais"],
+                "xkb_active_layout_index": 1
+            }
+        ]"#;
+
+        let layouts = client.parse_inputs_response(json);
+        assert_eq!(layouts.names[1], "Français");
+        assert_eq!(layouts.current_idx, 1);
+    }
+
+    #[test]
+    fn test_extract_focused_output() {
+        let client = create_test_client();
 
-        assert!(names.is_some());
-        let names = names.unwrap();
-        assert_eq!(names.len(), 3);
-        assert_eq!(names[0], "English");
-        assert_eq!(names[1], "Deutsch");
-        assert_eq!(names[2], "Francais");
+        let json = r#"{"change":"focus","current":{"type":"workspace","output":"HDMI-A-1"}}"#;
+        let output = client.extract_focused_output(json);
+        assert_eq!(output.as_deref(), Some("HDMI-A-1"));
     }
 
     #[test]
@@ -1,18 +1,28 @@
 use anyhow::{Context, Result};
+use async_channel::Sender;
 use gtk4_layer_shell::Edge;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
+/// How often the config watcher polls the file for changes.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 const DEFAULT_DISPLAY_TIMEOUT_MS: u64 = 2000;
 
 const DEFAULT_BUBBLE_TIMEOUT_MS: u64 = 10000;
 
 const DEFAULT_MAX_KEYS: usize = 5;
 
+const DEFAULT_CHORD_COALESCE_MS: u64 = 50;
+
 const DEFAULT_MARGIN: i32 = 20;
 
+const DEFAULT_SNAP_THRESHOLD: i32 = 24;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum DisplayMode {
@@ -21,6 +31,25 @@ pub enum DisplayMode {
     Bubble,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    #[default]
+    Default,
+    Compact,
+}
+
+/// A runtime action a keybind can trigger, independent of the launcher UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    TogglePause,
+    ShowLauncher,
+    SwitchMode,
+    Quit,
+    Clear,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Position {
@@ -89,6 +118,8 @@ pub struct Config {
 
     pub max_keys: usize,
 
+    pub chord_coalesce_ms: u64,
+
     pub margin: i32,
 
     pub show_modifiers: bool,
@@ -98,6 +129,36 @@ pub struct Config {
     pub font_scale: f64,
 
     pub opacity: f64,
+
+    pub density: Density,
+
+    pub bubble_corner_radius: f64,
+
+    pub bubble_shadow_blur: f64,
+
+    pub xkb_model: String,
+
+    pub xkb_layout: String,
+
+    pub xkb_variant: String,
+
+    pub xkb_options: Vec<String>,
+
+    pub snap_enabled: bool,
+
+    pub snap_threshold: i32,
+
+    pub follow_focus: bool,
+
+    pub pinned_output: String,
+
+    /// Monitor to place the overlay on: a connector name (`"DP-1"`), a zero-based
+    /// index, or `"focused"` to track the monitor holding the focused window.
+    /// Empty falls back to [`pinned_output`](Self::pinned_output) /
+    /// [`follow_focus`](Self::follow_focus).
+    pub output: String,
+
+    pub keybinds: HashMap<String, Action>,
 }
 
 impl Default for Config {
@@ -108,11 +169,25 @@ impl Default for Config {
             display_timeout_ms: DEFAULT_DISPLAY_TIMEOUT_MS,
             bubble_timeout_ms: DEFAULT_BUBBLE_TIMEOUT_MS,
             max_keys: DEFAULT_MAX_KEYS,
+            chord_coalesce_ms: DEFAULT_CHORD_COALESCE_MS,
             margin: DEFAULT_MARGIN,
             show_modifiers: true,
             all_keyboards: true,
             font_scale: 1.0,
             opacity: 0.9,
+            density: Density::Default,
+            bubble_corner_radius: 12.0,
+            bubble_shadow_blur: 8.0,
+            xkb_model: String::new(),
+            xkb_layout: String::new(),
+            xkb_variant: String::new(),
+            xkb_options: Vec::new(),
+            snap_enabled: true,
+            snap_threshold: DEFAULT_SNAP_THRESHOLD,
+            follow_focus: true,
+            pinned_output: String::new(),
+            output: String::new(),
+            keybinds: HashMap::new(),
         }
     }
 }
@@ -122,12 +197,7 @@ impl Config {
         let config_path = Self::config_path()?;
 
         if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .with_context(|| format!("Failed to read config: {:?}", config_path))?;
-
-            let config: Self =
-                toml::from_str(&content).with_context(|| "Failed to parse config file")?;
-
+            let config = Self::load_from(&config_path)?;
             info!("Loaded configuration from {:?}", config_path);
             Ok(config)
         } else {
@@ -136,6 +206,55 @@ impl Config {
         }
     }
 
+    /// Parse a config from a specific path without the default-on-missing
+    /// fallback, so callers (the loader and the watcher) share one parse path.
+    fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config: {:?}", path))?;
+
+        toml::from_str(&content).with_context(|| "Failed to parse config file")
+    }
+
+    /// Watch the config file and deliver each parsed-and-validated update over
+    /// `tx`. A background thread polls the file's modification time; on change it
+    /// re-reads the config, and only forwards it when it parses and validates.
+    /// Malformed or invalid reloads are logged and dropped so the running app
+    /// keeps its last good configuration. The thread exits when it next tries to
+    /// deliver an update after the receiver has been dropped.
+    pub fn watch(tx: Sender<Config>) -> Result<()> {
+        let path = Self::config_path()?;
+
+        std::thread::spawn(move || {
+            let mut last_seen = modified_time(&path);
+
+            loop {
+                std::thread::sleep(WATCH_POLL_INTERVAL);
+
+                let current = modified_time(&path);
+                if current == last_seen {
+                    continue;
+                }
+                last_seen = current;
+
+                match Self::load_from(&path) {
+                    Ok(config) => match config.validate() {
+                        Ok(()) => {
+                            info!("Reloading configuration from {:?}", path);
+                            if tx.send_blocking(config).is_err() {
+                                debug!("Config watcher stopping; receiver dropped");
+                                break;
+                            }
+                        }
+                        Err(e) => warn!("Ignoring invalid config reload: {}", e),
+                    },
+                    Err(e) => warn!("Failed to reload config, keeping current: {}", e),
+                }
+            }
+        });
+
+        Ok(())
+    }
+
     #[allow(dead_code)]
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
@@ -197,6 +316,12 @@ impl Config {
     }
 }
 
+/// The file's last-modified time, or `None` when it is missing or unreadable.
+/// Used by [`Config::watch`] to detect edits without parsing on every poll.
+fn modified_time(path: &Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +331,7 @@ mod tests {
         let config = Config::default();
         assert_eq!(config.display_timeout_ms, DEFAULT_DISPLAY_TIMEOUT_MS);
         assert_eq!(config.max_keys, DEFAULT_MAX_KEYS);
+        assert_eq!(config.chord_coalesce_ms, DEFAULT_CHORD_COALESCE_MS);
         assert!(config.validate().is_ok());
     }
 
@@ -216,6 +342,17 @@ mod tests {
         assert!(!edges.is_empty());
     }
 
+    #[test]
+    fn test_load_from_missing_path_errors() {
+        let result = Config::load_from(Path::new("/nonexistent/keystroke/config.toml"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_modified_time_missing_is_none() {
+        assert!(modified_time(Path::new("/nonexistent/keystroke/config.toml")).is_none());
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = Config::default();
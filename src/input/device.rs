@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use evdev::Device;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 #[derive(Debug, Clone)]
@@ -58,6 +58,102 @@ pub fn discover_keyboards() -> Result<Vec<KeyboardDevice>> {
     Ok(keyboards)
 }
 
+/// Probe a single `/dev/input` node and return a [`KeyboardDevice`] when it
+/// matches the same heuristics [`discover_keyboards`] uses. Used by the
+/// hot-plug watcher to evaluate nodes that appear after startup.
+pub fn probe_keyboard(path: &Path) -> Option<KeyboardDevice> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+
+    if !file_name.starts_with("event") {
+        return None;
+    }
+
+    match Device::open(path) {
+        Ok(device) if is_keyboard(&device) => {
+            let name = device.name().unwrap_or("Unknown Keyboard").to_string();
+            info!("Found keyboard: {} at {:?}", name, path);
+            Some(KeyboardDevice {
+                path: path.to_path_buf(),
+                name,
+            })
+        }
+        Ok(_) => None,
+        Err(e) => {
+            debug!("Could not open {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PointerDevice {
+    pub path: PathBuf,
+
+    pub name: String,
+}
+
+#[allow(dead_code)]
+impl PointerDevice {
+    pub fn open(&self) -> Result<Device> {
+        Device::open(&self.path).with_context(|| format!("Failed to open device: {:?}", self.path))
+    }
+}
+
+/// Enumerate pointing devices (mice, trackpads) the same way
+/// [`discover_keyboards`] enumerates keyboards, matching nodes that report
+/// relative-motion events and carry a left mouse button.
+#[allow(dead_code)]
+pub fn discover_pointers() -> Result<Vec<PointerDevice>> {
+    let mut pointers = Vec::new();
+    let input_dir = PathBuf::from("/dev/input");
+
+    let entries = fs::read_dir(&input_dir)
+        .with_context(|| format!("Failed to read directory: {:?}", input_dir))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        if !file_name.starts_with("event") {
+            continue;
+        }
+
+        match Device::open(&path) {
+            Ok(device) => {
+                if is_pointer(&device) {
+                    let name = device.name().unwrap_or("Unknown Pointer").to_string();
+
+                    info!("Found pointer: {} at {:?}", name, path);
+
+                    pointers.push(PointerDevice { path, name });
+                }
+            }
+            Err(e) => {
+                debug!("Could not open {:?}: {}", path, e);
+            }
+        }
+    }
+
+    if pointers.is_empty() {
+        warn!("No pointer devices found. Ensure you are in the 'input' group.");
+    }
+
+    Ok(pointers)
+}
+
+fn is_pointer(device: &Device) -> bool {
+    if !device.supported_events().contains(evdev::EventType::RELATIVE) {
+        return false;
+    }
+
+    device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(evdev::Key::BTN_LEFT))
+}
+
 fn is_keyboard(device: &Device) -> bool {
     let supported = device.supported_events();
     if !supported.contains(evdev::EventType::KEY) {
@@ -104,4 +200,10 @@ mod tests {
         let result = discover_keyboards();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_discover_pointers_returns_result() {
+        let result = discover_pointers();
+        assert!(result.is_ok());
+    }
 }
@@ -0,0 +1,117 @@
+use super::keymap::key_from_name;
+use evdev::Key;
+
+/// The set of command modifiers held for a keybind, collapsed across the
+/// left/right variants of each physical modifier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Mods {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub super_key: bool,
+    pub shift: bool,
+}
+
+impl Mods {
+    /// Track a modifier key going down or up. Non-modifier keys are ignored.
+    pub fn update(&mut self, key: Key, pressed: bool) {
+        match key {
+            Key::KEY_LEFTCTRL | Key::KEY_RIGHTCTRL => self.ctrl = pressed,
+            Key::KEY_LEFTALT | Key::KEY_RIGHTALT => self.alt = pressed,
+            Key::KEY_LEFTMETA | Key::KEY_RIGHTMETA => self.super_key = pressed,
+            Key::KEY_LEFTSHIFT | Key::KEY_RIGHTSHIFT => self.shift = pressed,
+            _ => {}
+        }
+    }
+}
+
+/// A parsed keybinding: a set of modifiers plus the non-modifier key that
+/// triggers it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keybind {
+    pub mods: Mods,
+    pub key: Key,
+}
+
+impl Keybind {
+    /// Parse a chord string such as `"Ctrl+Alt+p"`, `"Super+k"`, or `"Esc"`.
+    /// Modifier tokens are matched case-insensitively in any order; the single
+    /// remaining token names the trigger key via [`key_from_name`]. Returns
+    /// `None` when no key token is present, more than one is, or the key name is
+    /// unknown.
+    pub fn parse(chord: &str) -> Option<Self> {
+        let mut mods = Mods::default();
+        let mut key: Option<Key> = None;
+
+        for token in chord.split('+') {
+            let token = token.trim();
+            if token.is_empty() {
+                return None;
+            }
+
+            match token.to_lowercase().as_str() {
+                "ctrl" | "control" => mods.ctrl = true,
+                "alt" | "option" => mods.alt = true,
+                "super" | "meta" | "win" | "cmd" | "logo" => mods.super_key = true,
+                "shift" => mods.shift = true,
+                _ => {
+                    // A second non-modifier token is ambiguous.
+                    if key.is_some() {
+                        return None;
+                    }
+                    key = Some(key_from_name(token)?);
+                }
+            }
+        }
+
+        key.map(|key| Self { mods, key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modifier_chord() {
+        let bind = Keybind::parse("Ctrl+Alt+p").unwrap();
+        assert_eq!(
+            bind.mods,
+            Mods {
+                ctrl: true,
+                alt: true,
+                ..Mods::default()
+            }
+        );
+        assert_eq!(bind.key, Key::KEY_P);
+    }
+
+    #[test]
+    fn test_parse_super_chord_case_insensitive() {
+        let bind = Keybind::parse("super+K").unwrap();
+        assert!(bind.mods.super_key);
+        assert_eq!(bind.key, Key::KEY_K);
+    }
+
+    #[test]
+    fn test_parse_bare_key() {
+        let bind = Keybind::parse("Esc").unwrap();
+        assert_eq!(bind.mods, Mods::default());
+        assert_eq!(bind.key, Key::KEY_ESC);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_and_ambiguous() {
+        assert!(Keybind::parse("Ctrl+nope").is_none());
+        assert!(Keybind::parse("a+b").is_none());
+        assert!(Keybind::parse("Ctrl+").is_none());
+    }
+
+    #[test]
+    fn test_mods_update_collapses_left_right() {
+        let mut mods = Mods::default();
+        mods.update(Key::KEY_RIGHTCTRL, true);
+        assert!(mods.ctrl);
+        mods.update(Key::KEY_LEFTCTRL, false);
+        assert!(!mods.ctrl);
+    }
+}
@@ -1,6 +1,10 @@
+use anyhow::{Context, Result};
 use evdev::Key;
-use std::collections::HashMap;
-use std::sync::LazyLock;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock};
+use tracing::{debug, info};
 
 static KEY_NAMES: LazyLock<HashMap<Key, &'static str>> = LazyLock::new(|| {
     let mut m = HashMap::new();
@@ -163,13 +167,239 @@ impl KeyDisplay {
     }
 }
 
+/// Symbolic text labels for the keys that default to Nerd Font glyphs, used
+/// when the user asks for a glyph-free display (`symbolic_names = true`) so the
+/// overlay is legible in a terminal or on a system without the icon font.
+static SYMBOLIC_NAMES: LazyLock<HashMap<Key, &'static str>> = LazyLock::new(|| {
+    let mut m = HashMap::new();
+    m.insert(Key::KEY_LEFTCTRL, "Ctrl");
+    m.insert(Key::KEY_RIGHTCTRL, "Ctrl");
+    m.insert(Key::KEY_LEFTSHIFT, "Shift");
+    m.insert(Key::KEY_RIGHTSHIFT, "Shift");
+    m.insert(Key::KEY_LEFTALT, "Alt");
+    m.insert(Key::KEY_RIGHTALT, "Alt");
+    m.insert(Key::KEY_LEFTMETA, "Super");
+    m.insert(Key::KEY_RIGHTMETA, "Super");
+    m.insert(Key::KEY_CAPSLOCK, "Caps");
+    m.insert(Key::KEY_ESC, "Esc");
+    m.insert(Key::KEY_TAB, "Tab");
+    m.insert(Key::KEY_BACKSPACE, "Bksp");
+    m.insert(Key::KEY_ENTER, "Enter");
+    m.insert(Key::KEY_SPACE, "Space");
+    m.insert(Key::KEY_INSERT, "Ins");
+    m.insert(Key::KEY_DELETE, "Del");
+    m.insert(Key::KEY_HOME, "Home");
+    m.insert(Key::KEY_END, "End");
+    m.insert(Key::KEY_PAGEUP, "PgUp");
+    m.insert(Key::KEY_PAGEDOWN, "PgDn");
+    m.insert(Key::KEY_UP, "Up");
+    m.insert(Key::KEY_DOWN, "Down");
+    m.insert(Key::KEY_LEFT, "Left");
+    m.insert(Key::KEY_RIGHT, "Right");
+    m
+});
+
+/// Keys that never produce a typed character and are suppressed from the
+/// overlay by default: modifiers, locks, navigation, and function keys.
+static DEFAULT_IGNORED: LazyLock<HashSet<Key>> = LazyLock::new(|| {
+    [
+        Key::KEY_LEFTCTRL,
+        Key::KEY_RIGHTCTRL,
+        Key::KEY_LEFTALT,
+        Key::KEY_RIGHTALT,
+        Key::KEY_LEFTMETA,
+        Key::KEY_RIGHTMETA,
+        Key::KEY_LEFTSHIFT,
+        Key::KEY_RIGHTSHIFT,
+        Key::KEY_CAPSLOCK,
+        Key::KEY_NUMLOCK,
+        Key::KEY_SCROLLLOCK,
+        Key::KEY_FN,
+        Key::KEY_ESC,
+        Key::KEY_INSERT,
+        Key::KEY_HOME,
+        Key::KEY_END,
+        Key::KEY_PAGEUP,
+        Key::KEY_PAGEDOWN,
+        Key::KEY_UP,
+        Key::KEY_DOWN,
+        Key::KEY_LEFT,
+        Key::KEY_RIGHT,
+        Key::KEY_PRINT,
+        Key::KEY_PAUSE,
+        Key::KEY_F1,
+        Key::KEY_F2,
+        Key::KEY_F3,
+        Key::KEY_F4,
+        Key::KEY_F5,
+        Key::KEY_F6,
+        Key::KEY_F7,
+        Key::KEY_F8,
+        Key::KEY_F9,
+        Key::KEY_F10,
+        Key::KEY_F11,
+        Key::KEY_F12,
+    ]
+    .into_iter()
+    .collect()
+});
+
+/// Reverse lookup from an evdev key name (either the full `KEY_LEFTCTRL` form
+/// or the stripped `LEFTCTRL`) to its [`Key`], for resolving the names a user
+/// writes in the key-map TOML.
+static NAME_TO_KEY: LazyLock<HashMap<String, Key>> = LazyLock::new(|| {
+    let mut m = HashMap::new();
+    for key in KEY_NAMES.keys().chain(DEFAULT_IGNORED.iter()) {
+        let full = format!("{:?}", key);
+        let stripped = full.replace("KEY_", "");
+        m.insert(full, *key);
+        m.insert(stripped, *key);
+    }
+    m
+});
+
+/// Resolve an evdev key name (`KEY_LEFTCTRL`, `LEFTCTRL`, `A`, `ESC`, …) to its
+/// [`Key`]. Case-insensitive. Returns `None` for names the overlay doesn't know.
+pub fn key_from_name(name: &str) -> Option<Key> {
+    NAME_TO_KEY.get(&name.to_uppercase()).copied()
+}
+
+/// The effective display map, merged from the built-in defaults and the user's
+/// `keys.toml` at startup. Name lookups and ignore checks consult this when it
+/// has been installed; until then the compiled-in glyph table is used.
+static KEY_DISPLAY: OnceLock<KeyDisplayMap> = OnceLock::new();
+
+/// Display names and suppression set for rendered keys. Built from the
+/// compiled-in defaults and optionally overlaid with a user [`KeyMapConfig`].
+pub struct KeyDisplayMap {
+    names: HashMap<Key, String>,
+    ignored: HashSet<Key>,
+}
+
+impl KeyDisplayMap {
+    /// Build the default map. With `symbolic`, keys that normally render as a
+    /// Nerd Font glyph use their plain-text label from [`SYMBOLIC_NAMES`].
+    fn defaults(symbolic: bool) -> Self {
+        let mut names: HashMap<Key, String> = KEY_NAMES
+            .iter()
+            .map(|(k, v)| (*k, (*v).to_string()))
+            .collect();
+
+        if symbolic {
+            for (key, label) in SYMBOLIC_NAMES.iter() {
+                names.insert(*key, (*label).to_string());
+            }
+        }
+
+        Self {
+            names,
+            ignored: DEFAULT_IGNORED.clone(),
+        }
+    }
+
+    fn display_name(&self, key: Key) -> String {
+        self.names
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| format!("{:?}", key).replace("KEY_", ""))
+    }
+
+    fn is_ignored(&self, key: Key) -> bool {
+        self.ignored.contains(&key)
+    }
+}
+
+/// User overrides for the key-display map, parsed from `keys.toml`. The `[keys]`
+/// table maps an evdev key name to its display string and the `[ignore]` list
+/// names keys to suppress; `symbolic_names` swaps the glyph defaults for plain
+/// text before the overrides are applied.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct KeyMapConfig {
+    symbolic_names: bool,
+    keys: HashMap<String, String>,
+    ignore: Vec<String>,
+}
+
+impl KeyMapConfig {
+    /// Load `~/.config/keystroke/keys.toml`, falling back to an empty override
+    /// set when the file is absent or unreadable.
+    pub fn load_or_default() -> Self {
+        match Self::config_path().and_then(|path| Self::load(&path)) {
+            Ok(config) => config,
+            Err(e) => {
+                debug!("Using built-in key map: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    fn config_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(dir.join("keystroke").join("keys.toml"))
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read key map: {:?}", path))?;
+        let config: Self =
+            toml::from_str(&content).with_context(|| "Failed to parse keys.toml")?;
+        info!("Loaded key display map from {:?}", path);
+        Ok(config)
+    }
+
+    /// Merge the overrides over the built-in defaults into the runtime map.
+    /// Unknown key names are skipped so a typo never hides a working binding.
+    fn into_map(self) -> KeyDisplayMap {
+        let mut map = KeyDisplayMap::defaults(self.symbolic_names);
+
+        for (name, label) in self.keys {
+            if let Some(key) = key_from_name(&name) {
+                map.names.insert(key, label);
+            } else {
+                debug!("Ignoring unknown key name in keys.toml: {}", name);
+            }
+        }
+
+        for name in self.ignore {
+            if let Some(key) = key_from_name(&name) {
+                map.ignored.insert(key);
+            } else {
+                debug!("Ignoring unknown key name in keys.toml [ignore]: {}", name);
+            }
+        }
+
+        map
+    }
+}
+
+/// Install the user key-display map. Called once at startup after the config is
+/// loaded; later calls are ignored so the map stays stable for the process.
+pub fn init_key_display_map(config: KeyMapConfig) {
+    let _ = KEY_DISPLAY.set(config.into_map());
+}
+
 pub fn key_to_display_name(key: Key) -> String {
+    if let Some(map) = KEY_DISPLAY.get() {
+        return map.display_name(key);
+    }
+
     KEY_NAMES
         .get(&key)
         .map(|s| s.to_string())
         .unwrap_or_else(|| format!("{:?}", key).replace("KEY_", ""))
 }
 
+/// Whether `key` is suppressed from the overlay, honoring any user `[ignore]`
+/// list once the display map has been installed.
+pub fn is_ignored_key(key: Key) -> bool {
+    if let Some(map) = KEY_DISPLAY.get() {
+        return map.is_ignored(key);
+    }
+
+    DEFAULT_IGNORED.contains(&key)
+}
+
 pub fn is_modifier(key: Key) -> bool {
     matches!(
         key,
@@ -222,4 +452,31 @@ mod tests {
         assert_eq!(normalize_modifier(Key::KEY_RIGHTCTRL), Key::KEY_LEFTCTRL);
         assert_eq!(normalize_modifier(Key::KEY_A), Key::KEY_A);
     }
+
+    #[test]
+    fn test_key_from_name_accepts_both_forms() {
+        assert_eq!(key_from_name("KEY_LEFTCTRL"), Some(Key::KEY_LEFTCTRL));
+        assert_eq!(key_from_name("leftctrl"), Some(Key::KEY_LEFTCTRL));
+        assert_eq!(key_from_name("not_a_key"), None);
+    }
+
+    #[test]
+    fn test_symbolic_names_override_glyphs() {
+        let map = KeyDisplayMap::defaults(true);
+        assert_eq!(map.display_name(Key::KEY_LEFTCTRL), "Ctrl");
+        assert_eq!(map.display_name(Key::KEY_A), "A");
+    }
+
+    #[test]
+    fn test_config_overrides_merge_over_defaults() {
+        let config = KeyMapConfig {
+            symbolic_names: false,
+            keys: HashMap::from([("KEY_LEFTCTRL".to_string(), "Control".to_string())]),
+            ignore: vec!["KEY_SPACE".to_string()],
+        };
+        let map = config.into_map();
+        assert_eq!(map.display_name(Key::KEY_LEFTCTRL), "Control");
+        assert!(map.is_ignored(Key::KEY_SPACE));
+        assert!(map.is_ignored(Key::KEY_LEFTALT));
+    }
 }
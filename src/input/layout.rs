@@ -1,6 +1,6 @@
 use crate::compositor::{
-    self, hyprland::HyprlandClient, niri::NiriClient, sway::SwayClient, Compositor,
-    CompositorClient, KeyboardLayouts, LayoutEvent,
+    self, hyprland::HyprlandClient, mutter::MutterClient, niri::NiriClient, sway::SwayClient,
+    Compositor, CompositorClient, KeyboardLayouts, LayoutEvent,
 };
 use std::io::{BufRead, Read};
 use std::sync::{Arc, RwLock};
@@ -96,6 +96,17 @@ impl LayoutManager {
             .unwrap_or(0)
     }
 
+    /// Subscribe to the compositor's live layout-change events. The returned
+    /// receiver is driven from a background thread owned by the client. The app
+    /// itself does not call this; its layout badge is fed through
+    /// [`Self::start_listener`].
+    pub fn subscribe(&self) -> anyhow::Result<std::sync::mpsc::Receiver<LayoutEvent>> {
+        match &self.client {
+            Some(client) => client.subscribe(),
+            None => anyhow::bail!("no compositor client available for {}", self.compositor),
+        }
+    }
+
     pub fn refresh(&self) -> anyhow::Result<()> {
         let layouts = self.fetch_layouts()?;
         if let Ok(mut guard) = self.layouts.write() {
@@ -140,6 +151,9 @@ impl LayoutManager {
             Compositor::Sway => {
                 Self::listen_sway(layouts, stop_flag, callback);
             }
+            Compositor::Gnome => {
+                Self::listen_gnome(layouts, stop_flag, callback);
+            }
             _ => {
                 debug!("No event listener implemented for {}", compositor);
             }
@@ -349,6 +363,75 @@ impl LayoutManager {
 
         debug!("Sway event listener stopped");
     }
+
+    /// Watch GNOME's `input-sources` GSettings keys for layout changes. Runs a
+    /// dedicated GLib main loop on this thread so the `changed::current` /
+    /// `changed::sources` signals dispatch here; `stop_flag` is polled on a
+    /// timeout so [`stop_listener`](Self::stop_listener) can unwind it.
+    fn listen_gnome<F>(
+        layouts: Arc<RwLock<KeyboardLayouts>>,
+        stop_flag: Arc<std::sync::atomic::AtomicBool>,
+        callback: F,
+    ) where
+        F: Fn(LayoutEvent) + 'static,
+    {
+        use gtk4::glib;
+        use gtk4::prelude::*;
+
+        let settings = match MutterClient::open_settings() {
+            Some(s) => s,
+            None => {
+                warn!("GNOME input-sources schema unavailable for event listener");
+                return;
+            }
+        };
+
+        let context = glib::MainContext::new();
+        context.push_thread_default();
+
+        let main_loop = glib::MainLoop::new(Some(&context), false);
+
+        // Seed the cache and overlay with the current state before streaming.
+        let snapshot = MutterClient::read_layouts(&settings);
+        if let Ok(mut cached) = layouts.write() {
+            *cached = snapshot.clone();
+        }
+        callback(LayoutEvent::LayoutsChanged { layouts: snapshot });
+
+        let cache = Arc::clone(&layouts);
+        settings.connect_changed(None, move |settings, key| {
+            let new_layouts = MutterClient::read_layouts(settings);
+            if let Ok(mut cached) = cache.write() {
+                *cached = new_layouts.clone();
+            }
+
+            if key == "current" {
+                callback(LayoutEvent::LayoutSwitched {
+                    name: new_layouts.current_name().unwrap_or_default().to_string(),
+                    index: new_layouts.current_idx,
+                });
+            } else {
+                callback(LayoutEvent::LayoutsChanged {
+                    layouts: new_layouts,
+                });
+            }
+        });
+
+        let quit_loop = main_loop.clone();
+        glib::timeout_add_local(std::time::Duration::from_millis(200), move || {
+            if stop_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                quit_loop.quit();
+                glib::ControlFlow::Break
+            } else {
+                glib::ControlFlow::Continue
+            }
+        });
+
+        main_loop.run();
+        context.pop_thread_default();
+
+        debug!("GNOME event listener stopped");
+    }
 }
 
 impl Default for LayoutManager {
@@ -1,16 +1,27 @@
-use crate::input::device::{discover_keyboards, KeyboardDevice};
+use crate::input::device::{discover_keyboards, probe_keyboard, KeyboardDevice};
 use crate::input::keymap::KeyDisplay;
+use crate::input::output::VirtualKeyboard;
 use anyhow::{Context, Result};
-use async_channel::{Sender, TrySendError};
-use evdev::{Device, InputEventKind, Key};
-use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
-use std::collections::HashSet;
-use std::os::fd::{AsRawFd, BorrowedFd};
+use async_channel::{Receiver, Sender, TrySendError};
+use evdev::{AttributeSet, Device, InputEventKind, Key};
+use futures::Stream;
+use inotify::{Inotify, WatchMask};
+use nix::sys::epoll::{Epoll, EpollCreateFlags, EpollEvent, EpollFlags, EpollTimeout};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::os::fd::{AsFd, AsRawFd, RawFd};
+use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
 use std::thread;
+use std::time::{Duration, Instant};
 use tracing::{error, info, trace, warn};
 
+/// `epoll` token used for the inotify fd watching `/dev/input`; devices use
+/// their own raw fd as the token, which never collides with this sentinel.
+const INOTIFY_TOKEN: u64 = u64::MAX;
+
 #[derive(Debug, Clone)]
 pub enum KeyEvent {
     Pressed(KeyDisplay),
@@ -19,10 +30,40 @@ pub enum KeyEvent {
     AllReleased,
 }
 
+/// A mouse button, collapsing the kernel's `BTN_*` codes to the three the
+/// overlay renders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// A pointer action surfaced to the overlay, mirroring [`KeyEvent`]: button
+/// presses carry the [`PointerButton`], wheel motion is a direction.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub enum PointerEvent {
+    Down(PointerButton),
+    Up(PointerButton),
+    ScrollUp,
+    ScrollDown,
+}
+
 #[derive(Debug, Clone)]
 pub struct ListenerConfig {
     pub all_keyboards: bool,
     pub ignored_keys: HashSet<Key>,
+    pub hotplug: bool,
+    /// When set, the listener enters intercept mode: it grabs every source
+    /// device exclusively, runs each key through the remap table, and re-emits
+    /// the result through a `uinput` virtual keyboard.
+    pub remap: Option<RemapConfig>,
+    /// When set, the listener synthesizes key repeats with this delay and rate
+    /// instead of forwarding the kernel's hardware auto-repeat. `None` (the
+    /// default) passes hardware repeats through unchanged.
+    pub repeat: Option<RepeatConfig>,
 }
 
 impl Default for ListenerConfig {
@@ -30,8 +71,61 @@ impl Default for ListenerConfig {
         Self {
             all_keyboards: true,
             ignored_keys: HashSet::new(),
+            hotplug: false,
+            remap: None,
+            repeat: None,
+        }
+    }
+}
+
+/// Parameters for software-synthesized key repeat.
+#[derive(Debug, Clone)]
+pub struct RepeatConfig {
+    /// Delay before the first synthetic repeat fires, in milliseconds.
+    pub repeat_delay_ms: u64,
+    /// Repeat frequency in hertz; the inter-repeat interval is `1000 / rate` ms.
+    pub repeat_rate_hz: f64,
+}
+
+impl Default for RepeatConfig {
+    fn default() -> Self {
+        Self {
+            repeat_delay_ms: 250,
+            repeat_rate_hz: 25.0,
+        }
+    }
+}
+
+/// Translation table applied in intercept mode. Each source [`Key`] maps to
+/// either a replacement key or [`None`], which drops the key entirely (a
+/// stronger form of `ignored_keys` that also suppresses the re-emitted event).
+#[derive(Debug, Clone, Default)]
+pub struct RemapConfig {
+    pub map: HashMap<Key, Option<Key>>,
+}
+
+impl RemapConfig {
+    /// Resolve a source key to the key that should be emitted, or `None` when
+    /// the key is explicitly ignored. Unmapped keys pass through unchanged.
+    fn resolve(&self, key: Key) -> Option<Key> {
+        match self.map.get(&key) {
+            Some(target) => *target,
+            None => Some(key),
         }
     }
+
+    /// Union of every key code the output device must be able to emit: the
+    /// pass-through sources plus every substitution target.
+    fn emittable_keys(&self) -> AttributeSet<Key> {
+        let mut keys = AttributeSet::<Key>::new();
+        for (src, target) in &self.map {
+            keys.insert(*src);
+            if let Some(target) = target {
+                keys.insert(*target);
+            }
+        }
+        keys
+    }
 }
 
 pub struct ListenerHandle {
@@ -74,23 +168,44 @@ impl KeyListener {
 
         self.running.store(true, Ordering::SeqCst);
 
-        for keyboard in devices_to_use {
-            let sender = self.sender.clone();
-            let running = Arc::clone(&self.running);
-            let ignored_keys = self.config.ignored_keys.clone();
-
-            thread::spawn(move || {
-                if let Err(e) = listen_to_device(keyboard, sender, running, ignored_keys) {
-                    error!("Keyboard listener error: {}", e);
-                }
-            });
-        }
+        let sender = self.sender.clone();
+        let running = Arc::clone(&self.running);
+        let ignored_keys = self.config.ignored_keys.clone();
+        let hotplug = self.config.hotplug;
+        let remap = self.config.remap.clone();
+        let repeat = self.config.repeat.clone();
+
+        thread::spawn(move || {
+            if let Err(e) = run_event_loop(
+                devices_to_use,
+                sender,
+                running,
+                ignored_keys,
+                hotplug,
+                remap,
+                repeat,
+            ) {
+                error!("Keyboard event loop error: {}", e);
+            }
+        });
 
         Ok(ListenerHandle {
             running: self.running.clone(),
         })
     }
 
+    /// Create the event channel internally and start listening, returning the
+    /// receiving half as a [`KeyEventStream`] together with the owning
+    /// [`ListenerHandle`]. Dropping the handle stops the listener; the stream
+    /// then ends once the channel drains. The [`new`](Self::new) path remains
+    /// for callers that want to supply their own sender.
+    pub fn start_stream(config: ListenerConfig) -> Result<(KeyEventStream, ListenerHandle)> {
+        let (sender, receiver) = async_channel::bounded(256);
+        let listener = Self::new(sender, config);
+        let handle = listener.start()?;
+        Ok((KeyEventStream { receiver }, handle))
+    }
+
     #[allow(dead_code)]
     pub fn stop(&self) {
         self.running.store(false, Ordering::SeqCst);
@@ -102,62 +217,404 @@ impl KeyListener {
     }
 }
 
-fn listen_to_device(
+/// Drives software key repeat. Each held key, modifiers included, has a
+/// next-fire deadline in `deadlines`; the loop wakes at the earliest one, emits a
+/// synthetic repeat, and reschedules it `period` later until the key is
+/// released. Kernel value-`2` events are suppressed while this is active.
+struct RepeatEngine {
+    delay: Duration,
+    period: Duration,
+    deadlines: BTreeMap<Instant, Key>,
+    scheduled: HashMap<Key, Instant>,
+}
+
+impl RepeatEngine {
+    fn new(config: &RepeatConfig) -> Self {
+        let rate = config.repeat_rate_hz.max(1.0);
+        Self {
+            delay: Duration::from_millis(config.repeat_delay_ms),
+            period: Duration::from_secs_f64(1.0 / rate),
+            deadlines: BTreeMap::new(),
+            scheduled: HashMap::new(),
+        }
+    }
+
+    /// Schedule the first repeat for a freshly pressed key.
+    fn press(&mut self, key: Key) {
+        self.reschedule(key, Instant::now() + self.delay);
+    }
+
+    /// Cancel any pending repeats for a released key.
+    fn release(&mut self, key: Key) {
+        if let Some(deadline) = self.scheduled.remove(&key) {
+            self.deadlines.remove(&deadline);
+        }
+    }
+
+    fn reschedule(&mut self, key: Key, mut at: Instant) {
+        if let Some(prev) = self.scheduled.remove(&key) {
+            self.deadlines.remove(&prev);
+        }
+        // Keys are keyed by their deadline; nudge on the rare collision so no
+        // pending repeat is silently dropped.
+        while self.deadlines.contains_key(&at) {
+            at += Duration::from_nanos(1);
+        }
+        self.deadlines.insert(at, key);
+        self.scheduled.insert(key, at);
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.deadlines.keys().next().copied()
+    }
+
+    /// Pop every deadline at or before `now`, returning the keys to repeat, and
+    /// reschedule each one `period` into the future.
+    fn drain_due(&mut self, now: Instant) -> Vec<Key> {
+        let mut fired = Vec::new();
+        while let Some((&deadline, &key)) = self.deadlines.iter().next() {
+            if deadline > now {
+                break;
+            }
+            self.deadlines.remove(&deadline);
+            self.scheduled.remove(&key);
+            fired.push(key);
+        }
+        for &key in &fired {
+            self.reschedule(key, now + self.period);
+        }
+        fired
+    }
+}
+
+/// A [`Stream`] of [`KeyEvent`]s yielding a value on each press/release
+/// transition. Lets consumers write `while let Some(ev) = stream.next().await`
+/// instead of driving the raw channel receiver by hand.
+pub struct KeyEventStream {
+    receiver: Receiver<KeyEvent>,
+}
+
+impl Stream for KeyEventStream {
+    type Item = KeyEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().receiver).poll_next(cx)
+    }
+}
+
+/// Per-device state owned by the event loop: the open evdev handle plus the set
+/// of keys we currently believe to be held, used for stuck-key reconciliation.
+struct DeviceState {
+    name: String,
+    path: PathBuf,
+    device: Device,
+    pressed_keys: HashSet<Key>,
+}
+
+/// Register a keyboard with the epoll set, keyed by its raw fd, and record its
+/// [`DeviceState`]. Silently skips devices that fail to open or register.
+fn register_device(
+    epoll: &Epoll,
+    devices: &mut HashMap<RawFd, DeviceState>,
     keyboard: KeyboardDevice,
+) {
+    if devices.values().any(|s| s.path == keyboard.path) {
+        return;
+    }
+
+    let device = match keyboard.open() {
+        Ok(d) => d,
+        Err(e) => {
+            warn!("Failed to open keyboard {}: {}", keyboard.name, e);
+            return;
+        }
+    };
+
+    let raw_fd = device.as_raw_fd();
+    let event = EpollEvent::new(EpollFlags::EPOLLIN, raw_fd as u64);
+
+    if let Err(e) = epoll.add(device.as_fd(), event) {
+        warn!("Failed to register {} with epoll: {}", keyboard.name, e);
+        return;
+    }
+
+    info!("Listening to keyboard: {}", keyboard.name);
+    devices.insert(
+        raw_fd,
+        DeviceState {
+            name: keyboard.name,
+            path: keyboard.path,
+            device,
+            pressed_keys: HashSet::new(),
+        },
+    );
+}
+
+/// Remove a device from the epoll set and drop its state, emitting a release
+/// for every key we still believed to be held so the overlay doesn't stick.
+fn deregister_device(
+    epoll: &Epoll,
+    devices: &mut HashMap<RawFd, DeviceState>,
+    fd: RawFd,
+    sender: &Sender<KeyEvent>,
+) {
+    if let Some(state) = devices.remove(&fd) {
+        let _ = epoll.delete(state.device.as_fd());
+        for key in &state.pressed_keys {
+            let _ = sender.try_send(KeyEvent::Released(KeyDisplay::new(*key, false)));
+        }
+        info!("Stopped listening to keyboard: {}", state.name);
+    }
+}
+
+/// Owns a single `epoll` instance watching every discovered keyboard fd and
+/// dispatches ready devices to [`process_events`]. Replaces the previous
+/// one-thread-per-device polling design so shutdown is a single cooperative
+/// flag and idle devices cost nothing. When `hotplug` is set an inotify watch
+/// on `/dev/input` keeps the device set in sync with reconnects.
+fn run_event_loop(
+    keyboards: Vec<KeyboardDevice>,
     sender: Sender<KeyEvent>,
     running: Arc<AtomicBool>,
     ignored_keys: HashSet<Key>,
+    hotplug: bool,
+    remap: Option<RemapConfig>,
+    repeat: Option<RepeatConfig>,
 ) -> Result<()> {
-    let mut device = keyboard.open()?;
-    info!("Listening to keyboard: {}", keyboard.name);
+    let epoll = Epoll::new(EpollCreateFlags::empty()).context("Failed to create epoll instance")?;
 
-    let raw_fd = device.as_raw_fd();
+    let mut devices: HashMap<RawFd, DeviceState> = HashMap::new();
 
-    let borrowed_fd = unsafe { BorrowedFd::borrow_raw(raw_fd) };
-    let mut poll_fds = [PollFd::new(borrowed_fd, PollFlags::POLLIN)];
-    let mut pressed_keys = HashSet::new();
+    for keyboard in keyboards {
+        register_device(&epoll, &mut devices, keyboard);
+    }
 
-    while running.load(Ordering::SeqCst) {
-        let poll_result = poll(&mut poll_fds, PollTimeout::try_from(100).unwrap());
-
-        match poll_result {
-            Ok(_n) => {
-                if let Err(e) =
-                    process_events(&mut device, &sender, &ignored_keys, &mut pressed_keys)
-                {
-                    if e.to_string().contains("Channel closed") {
-                        info!("Channel closed, stopping listener for {}", keyboard.name);
-                        break;
-                    }
-                    warn!("Error processing events: {}", e);
+    if devices.is_empty() {
+        anyhow::bail!("No keyboard devices could be opened for listening");
+    }
+
+    // Intercept mode: grab every source device exclusively and build the
+    // virtual output before we start forwarding events.
+    let mut output = match &remap {
+        Some(remap) => {
+            let keyboard = VirtualKeyboard::new(&remap.emittable_keys())?;
+            for state in devices.values_mut() {
+                if let Err(e) = state.device.grab() {
+                    warn!("Failed to grab {} for intercept mode: {}", state.name, e);
                 }
             }
+            Some(keyboard)
+        }
+        None => None,
+    };
+
+    let inotify = if hotplug {
+        match setup_hotplug_watch(&epoll) {
+            Ok(i) => Some(i),
+            Err(e) => {
+                warn!("Hot-plug detection disabled: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut repeat_engine = repeat.as_ref().map(RepeatEngine::new);
+
+    // Sized generously so a burst of device and inotify readiness fits one wait.
+    let mut events = vec![EpollEvent::empty(); devices.len() + 4];
+    let mut inotify_buf = [0u8; 1024];
+    let mut dead_fds: Vec<RawFd> = Vec::new();
+
+    while running.load(Ordering::SeqCst) {
+        // Wake no later than the next pending repeat, but never idle more than
+        // 100ms so the stop flag stays responsive.
+        let timeout_ms = repeat_engine
+            .as_ref()
+            .and_then(RepeatEngine::next_deadline)
+            .map(|d| {
+                d.saturating_duration_since(Instant::now())
+                    .as_millis()
+                    .min(100) as u16
+            })
+            .unwrap_or(100);
+
+        let ready = match epoll.wait(&mut events, EpollTimeout::try_from(timeout_ms).unwrap()) {
+            Ok(n) => n,
+            Err(nix::errno::Errno::EINTR) => continue,
             Err(e) => {
-                error!("Poll error: {}", e);
+                error!("epoll_wait error: {}", e);
                 break;
             }
+        };
+
+        for event in events.iter().take(ready) {
+            if event.data() == INOTIFY_TOKEN {
+                if let Some(inotify) = &mut inotify {
+                    handle_hotplug_events(inotify, &mut inotify_buf, &epoll, &mut devices, &sender);
+                }
+                continue;
+            }
+
+            let fd = event.data() as RawFd;
+
+            let Some(state) = devices.get_mut(&fd) else {
+                continue;
+            };
+
+            if let Err(e) = process_events(
+                &mut state.device,
+                &sender,
+                &ignored_keys,
+                &mut state.pressed_keys,
+                remap.as_ref().zip(output.as_mut()),
+                repeat_engine.as_mut(),
+            ) {
+                if e.to_string().contains("Channel closed") {
+                    info!("Channel closed, stopping listener");
+                    running.store(false, Ordering::SeqCst);
+                    break;
+                }
+                if is_device_gone(&e) {
+                    info!("Keyboard {} disappeared, dropping it", state.name);
+                    dead_fds.push(fd);
+                } else {
+                    warn!("Error processing events for {}: {}", state.name, e);
+                }
+            }
+        }
+
+        for fd in dead_fds.drain(..) {
+            deregister_device(&epoll, &mut devices, fd, &sender);
+        }
+
+        if let Some(engine) = repeat_engine.as_mut() {
+            for key in engine.drain_due(Instant::now()) {
+                let repeat = KeyEvent::Pressed(KeyDisplay::new_repeat(key));
+                if let Err(TrySendError::Closed(_)) = sender.try_send(repeat) {
+                    running.store(false, Ordering::SeqCst);
+                    break;
+                }
+            }
+        }
+
+        if hotplug && devices.is_empty() {
+            // Keep the loop alive so a reconnect can be picked up by inotify.
+            continue;
         }
     }
 
-    info!("Stopped listening to keyboard: {}", keyboard.name);
+    info!("Stopped keyboard event loop");
     Ok(())
 }
 
+fn setup_hotplug_watch(epoll: &Epoll) -> Result<Inotify> {
+    let inotify = Inotify::init().context("Failed to initialize inotify")?;
+    inotify
+        .watches()
+        .add(
+            "/dev/input",
+            WatchMask::CREATE | WatchMask::ATTRIB | WatchMask::DELETE,
+        )
+        .context("Failed to watch /dev/input")?;
+
+    let event = EpollEvent::new(EpollFlags::EPOLLIN, INOTIFY_TOKEN);
+    epoll
+        .add(inotify.as_fd(), event)
+        .context("Failed to register inotify with epoll")?;
+
+    info!("Hot-plug detection enabled on /dev/input");
+    Ok(inotify)
+}
+
+fn handle_hotplug_events(
+    inotify: &mut Inotify,
+    buf: &mut [u8],
+    epoll: &Epoll,
+    devices: &mut HashMap<RawFd, DeviceState>,
+    sender: &Sender<KeyEvent>,
+) {
+    let events = match inotify.read_events(buf) {
+        Ok(events) => events,
+        Err(e) => {
+            debug_assert!(e.kind() != std::io::ErrorKind::InvalidInput);
+            return;
+        }
+    };
+
+    for event in events {
+        let Some(name) = event.name.and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        let path = PathBuf::from("/dev/input").join(name);
+
+        if event.mask.contains(inotify::EventMask::DELETE) {
+            if let Some(fd) = devices
+                .iter()
+                .find(|(_, s)| s.path == path)
+                .map(|(fd, _)| *fd)
+            {
+                deregister_device(epoll, devices, fd, sender);
+            }
+            continue;
+        }
+
+        if let Some(keyboard) = probe_keyboard(&path) {
+            register_device(epoll, devices, keyboard);
+        }
+    }
+}
+
+/// True when an event-processing error is rooted in an `ENODEV` read, i.e. the
+/// device node was removed while we held it open.
+fn is_device_gone(err: &anyhow::Error) -> bool {
+    err.root_cause()
+        .downcast_ref::<std::io::Error>()
+        .and_then(|e| e.raw_os_error())
+        == Some(nix::errno::Errno::ENODEV as i32)
+}
+
 fn process_events(
     device: &mut Device,
     sender: &Sender<KeyEvent>,
     ignored_keys: &HashSet<Key>,
     pressed_keys: &mut HashSet<Key>,
+    mut intercept: Option<(&RemapConfig, &mut VirtualKeyboard)>,
+    mut repeat: Option<&mut RepeatEngine>,
 ) -> Result<()> {
     let events = device.fetch_events().context("Failed to fetch events")?;
     let mut activity = false;
 
     for event in events {
         if let InputEventKind::Key(key) = event.kind() {
+            // In intercept mode we took an exclusive grab, so re-emit the
+            // (remapped) key through the virtual output regardless of whether
+            // it is visualized below.
+            if let Some((remap, output)) = intercept.as_mut() {
+                if let Some(target) = remap.resolve(key) {
+                    if let Err(e) = output.emit(target, event.value()) {
+                        warn!("Failed to re-emit remapped key: {}", e);
+                    }
+                }
+            }
+
             if ignored_keys.contains(&key) {
                 continue;
             }
 
+            // When software repeat owns timing, track presses/releases and drop
+            // the kernel's own value-2 events to avoid double repeats.
+            if let Some(engine) = repeat.as_deref_mut() {
+                match event.value() {
+                    1 => engine.press(key),
+                    0 => engine.release(key),
+                    2 => continue,
+                    _ => {}
+                }
+            }
+
             activity = true;
             let key_event = match event.value() {
                 1 => {
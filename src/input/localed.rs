@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::LazyLock;
+
+use super::xkb::XkbState;
+
+/// System X11 keyboard configuration as exposed by `localectl`/`localed`,
+/// sourced from `/etc/default/keyboard` (Debian) or `/etc/vconsole.conf`
+/// (systemd). When neither file is present the same values can be queried from
+/// `org.freedesktop.locale1` over D-Bus; that path is not wired up here.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct X11Context {
+    pub layout: String,
+    pub variant: String,
+    pub model: String,
+    pub options: String,
+}
+
+/// Converted keymaps: the set of `layout[-variant]` names for which a console
+/// keymap has been generated from the X11 data. `localed` consults this first
+/// before scanning the legacy table.
+static CONVERTED_KEYMAPS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    vec![
+        "us", "uk", "de", "de-nodeadkeys", "fr", "fr-latin9", "es", "it", "pt",
+        "br", "br-abnt2", "pl", "pl-dvorak", "ru", "se", "no", "dk", "fi", "cz",
+        "cz-qwerty", "sk", "hu", "tr", "gr", "nl", "be", "ch", "ch-fr", "latam",
+    ]
+});
+
+/// A legacy console keymap and the X11 attributes it corresponds to, used to
+/// score candidates when no converted keymap matches.
+struct LegacyEntry {
+    keymap: &'static str,
+    layout: &'static str,
+    variant: &'static str,
+    model: &'static str,
+    options: &'static str,
+}
+
+static LEGACY_KEYMAPS: LazyLock<Vec<LegacyEntry>> = LazyLock::new(|| {
+    vec![
+        LegacyEntry { keymap: "us", layout: "us", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "dvorak", layout: "us", variant: "dvorak", model: "pc105", options: "" },
+        LegacyEntry { keymap: "uk", layout: "gb", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "de", layout: "de", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "de-latin1-nodeadkeys", layout: "de", variant: "nodeadkeys", model: "pc105", options: "" },
+        LegacyEntry { keymap: "fr", layout: "fr", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "es", layout: "es", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "it", layout: "it", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "pl", layout: "pl", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "ru", layout: "ru", variant: "", model: "pc105", options: "" },
+        LegacyEntry { keymap: "la-latin1", layout: "latam", variant: "", model: "pc105", options: "" },
+    ]
+});
+
+impl X11Context {
+    /// Read the system keyboard configuration, preferring `/etc/default/keyboard`
+    /// and falling back to `/etc/vconsole.conf`.
+    #[must_use]
+    pub fn from_system() -> Option<Self> {
+        for path in ["/etc/default/keyboard", "/etc/vconsole.conf"] {
+            if let Ok(contents) = fs::read_to_string(path) {
+                let ctx = Self::parse(&contents);
+                if !ctx.layout.is_empty() {
+                    tracing::debug!("Loaded X11 keyboard config from {}: {:?}", path, ctx);
+                    return Some(ctx);
+                }
+            }
+        }
+
+        tracing::debug!("No system X11 keyboard configuration found");
+        None
+    }
+
+    /// Parse the `KEY="value"` shell-style assignments both config files use,
+    /// honoring the `XKB`-prefixed keys.
+    fn parse(contents: &str) -> Self {
+        let mut values: HashMap<&str, String> = HashMap::new();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim().trim_matches('"').trim_matches('\'');
+                values.insert(key.trim(), value.to_string());
+            }
+        }
+
+        Self {
+            layout: values.remove("XKBLAYOUT").unwrap_or_default(),
+            variant: values.remove("XKBVARIANT").unwrap_or_default(),
+            model: values.remove("XKBMODEL").unwrap_or_default(),
+            options: values.remove("XKBOPTIONS").unwrap_or_default(),
+        }
+    }
+
+    /// Resolve this context to an installed console keymap name the way
+    /// `localed` does: try a converted keymap first, then fall back to the
+    /// best-scoring legacy entry.
+    #[must_use]
+    pub fn resolve_keymap(&self) -> Option<String> {
+        if let Some(name) = find_converted_keymap(&self.layout, &self.variant) {
+            return Some(name);
+        }
+        find_legacy_keymap(self).map(str::to_string)
+    }
+
+    /// Build an [`XkbState`] for this context, feeding the resolved layout and
+    /// variant through the normal XKB path. When a variant is set but no
+    /// converted keymap exists for it, fall through to the bare-layout keymap.
+    #[must_use]
+    pub fn to_xkb_state(&self) -> Option<XkbState> {
+        if self.layout.is_empty() {
+            return None;
+        }
+
+        let has_converted = find_converted_keymap(&self.layout, &self.variant).is_some();
+
+        // Keep the variant only when a converted keymap backs it; otherwise
+        // fall through to the bare-layout keymap rather than failing.
+        let name = if !self.variant.is_empty() && has_converted {
+            format!("{}+{}", self.layout, self.variant)
+        } else {
+            self.layout.clone()
+        };
+
+        XkbState::from_layout_name(Some(&name))
+    }
+}
+
+/// Join layout and variant with a dash (`pl`+`dvorak` → `pl-dvorak`, bare `pl`
+/// → `pl`) and return the name if a converted keymap exists for it.
+fn find_converted_keymap(layout: &str, variant: &str) -> Option<String> {
+    if layout.is_empty() {
+        return None;
+    }
+
+    let name = if variant.is_empty() {
+        layout.to_string()
+    } else {
+        format!("{}-{}", layout, variant)
+    };
+
+    CONVERTED_KEYMAPS
+        .iter()
+        .any(|k| *k == name)
+        .then_some(name)
+}
+
+/// Scan the legacy table, scoring each entry by how many of layout/variant/
+/// model/options match, and prefer exact layout matches.
+fn find_legacy_keymap(ctx: &X11Context) -> Option<&'static str> {
+    let mut best: Option<(&'static str, u32)> = None;
+
+    for entry in LEGACY_KEYMAPS.iter() {
+        if entry.layout != ctx.layout {
+            continue;
+        }
+
+        let mut score = 2; // exact layout match
+        if entry.variant == ctx.variant {
+            score += 1;
+        }
+        if entry.model == ctx.model {
+            score += 1;
+        }
+        if entry.options == ctx.options {
+            score += 1;
+        }
+
+        if best.map(|(_, s)| score > s).unwrap_or(true) {
+            best = Some((entry.keymap, score));
+        }
+    }
+
+    best.map(|(keymap, _)| keymap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_keyboard() {
+        let contents = r#"
+            # KEYBOARD CONFIGURATION FILE
+            XKBMODEL="pc105"
+            XKBLAYOUT="de"
+            XKBVARIANT="nodeadkeys"
+            XKBOPTIONS="caps:swapescape"
+        "#;
+
+        let ctx = X11Context::parse(contents);
+        assert_eq!(ctx.layout, "de");
+        assert_eq!(ctx.variant, "nodeadkeys");
+        assert_eq!(ctx.model, "pc105");
+        assert_eq!(ctx.options, "caps:swapescape");
+    }
+
+    #[test]
+    fn test_converted_keymap_with_variant() {
+        assert_eq!(find_converted_keymap("pl", "dvorak"), Some("pl-dvorak".to_string()));
+        assert_eq!(find_converted_keymap("pl", ""), Some("pl".to_string()));
+        assert_eq!(find_converted_keymap("xx", ""), None);
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_legacy() {
+        let ctx = X11Context {
+            layout: "us".to_string(),
+            variant: "dvorak".to_string(),
+            model: "pc105".to_string(),
+            options: String::new(),
+        };
+        // us-dvorak is not a converted keymap, so the legacy `dvorak` entry wins.
+        assert_eq!(ctx.resolve_keymap(), Some("dvorak".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_prefers_converted() {
+        let ctx = X11Context {
+            layout: "de".to_string(),
+            variant: "nodeadkeys".to_string(),
+            ..X11Context::default()
+        };
+        assert_eq!(ctx.resolve_keymap(), Some("de-nodeadkeys".to_string()));
+    }
+}
@@ -1,6 +1,20 @@
 pub mod device;
+pub mod keybind;
 pub mod keymap;
+pub mod layout;
 pub mod listener;
+pub mod localed;
+pub mod output;
+pub mod xkb;
 
-pub use keymap::{is_modifier, KeyDisplay};
-pub use listener::{KeyEvent, KeyListener, ListenerConfig};
+pub use keybind::{Keybind, Mods};
+
+pub use keymap::{
+    init_key_display_map, is_ignored_key, is_modifier, key_to_display_name, normalize_modifier,
+    KeyDisplay, KeyMapConfig,
+};
+pub use listener::{
+    KeyEvent, KeyListener, ListenerConfig, PointerButton, PointerEvent, RemapConfig, RepeatConfig,
+};
+pub use localed::X11Context;
+pub use xkb::{Compose, XkbConfig, XkbState};
@@ -0,0 +1,36 @@
+use anyhow::{Context, Result};
+use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use evdev::{AttributeSet, EventType, InputEvent, Key};
+use tracing::info;
+
+/// A `uinput`-backed virtual keyboard used by the listener's intercept mode to
+/// re-emit (optionally remapped) key events after grabbing the physical
+/// devices. The set of emittable keys is fixed at construction from the union
+/// of every code the remap table can produce.
+pub struct VirtualKeyboard {
+    device: VirtualDevice,
+}
+
+impl VirtualKeyboard {
+    pub fn new(keys: &AttributeSet<Key>) -> Result<Self> {
+        let device = VirtualDeviceBuilder::new()
+            .context("Failed to create uinput builder")?
+            .name("keystroke-virtual-keyboard")
+            .with_keys(keys)
+            .context("Failed to declare virtual keyboard keys")?
+            .build()
+            .context("Failed to build uinput virtual keyboard")?;
+
+        info!("Created uinput virtual keyboard");
+        Ok(Self { device })
+    }
+
+    /// Emit a single key transition (`value` follows evdev semantics: 0 release,
+    /// 1 press, 2 repeat). The kernel synthesizes the trailing `SYN_REPORT`.
+    pub fn emit(&mut self, key: Key, value: i32) -> Result<()> {
+        let event = InputEvent::new(EventType::KEY, key.code(), value);
+        self.device
+            .emit(&[event])
+            .context("Failed to emit virtual key event")
+    }
+}
@@ -1,5 +1,8 @@
 use evdev::Key;
+use nix::sys::mman::{mmap, munmap, MapFlags, ProtFlags};
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::os::fd::{BorrowedFd, RawFd};
 use std::sync::LazyLock;
 use xkbcommon::xkb;
 
@@ -107,6 +110,121 @@ static LAYOUT_NAME_MAP: LazyLock<HashMap<&'static str, (&'static str, &'static s
         m
     });
 
+/// Prioritized layout candidates per locale, keyed by the first five characters
+/// of the locale (`es_MX`, `de_AT`) with a two-letter language prefix (`es`,
+/// `de`) as a fallback key. Each candidate is `(layout, variant, priority)`;
+/// the highest-priority one whose keymap actually compiles wins.
+static LANG_TO_LAYOUT: LazyLock<HashMap<&'static str, Vec<(&'static str, &'static str, u8)>>> =
+    LazyLock::new(|| {
+        let mut m = HashMap::new();
+
+        m.insert("es_MX", vec![("latam", "", 80), ("us", "intl", 50), ("es", "", 20)]);
+        m.insert("es_AR", vec![("latam", "", 80), ("es", "", 20)]);
+        m.insert("es_ES", vec![("es", "", 80), ("us", "intl", 30)]);
+        m.insert("es", vec![("es", "", 60), ("latam", "", 40)]);
+
+        m.insert("de_AT", vec![("de", "nodeadkeys", 70), ("de", "", 50)]);
+        m.insert("de_CH", vec![("ch", "de", 70), ("de", "", 40)]);
+        m.insert("de", vec![("de", "", 70), ("de", "nodeadkeys", 40)]);
+
+        m.insert("fr_BE", vec![("be", "", 70), ("fr", "", 40)]);
+        m.insert("fr_CA", vec![("ca", "fr", 70), ("fr", "", 40)]);
+        m.insert("fr_CH", vec![("ch", "fr", 70), ("fr", "", 40)]);
+        m.insert("fr", vec![("fr", "", 70)]);
+
+        m.insert("pt_BR", vec![("br", "", 80), ("pt", "", 30)]);
+        m.insert("pt", vec![("pt", "", 70)]);
+
+        m.insert("en_GB", vec![("gb", "", 80), ("us", "", 30)]);
+        m.insert("en_US", vec![("us", "", 80)]);
+        m.insert("en", vec![("us", "", 60)]);
+
+        m.insert("bg", vec![("bg", "phonetic", 60), ("bg", "", 50)]);
+        m.insert("ru", vec![("ru", "", 70), ("ru", "phonetic", 40)]);
+        m.insert("ua", vec![("ua", "", 70)]);
+        m.insert("uk_UA", vec![("ua", "", 70)]);
+
+        m.insert("it", vec![("it", "", 70)]);
+        m.insert("pl", vec![("pl", "", 70)]);
+        m.insert("cs", vec![("cz", "", 70)]);
+        m.insert("cz", vec![("cz", "", 70)]);
+        m.insert("sk", vec![("sk", "", 70)]);
+        m.insert("hu", vec![("hu", "", 70)]);
+        m.insert("tr", vec![("tr", "", 70)]);
+        m.insert("el", vec![("gr", "", 70)]);
+        m.insert("gr", vec![("gr", "", 70)]);
+        m.insert("nl", vec![("nl", "", 70)]);
+        m.insert("sv", vec![("se", "", 70)]);
+        m.insert("nb", vec![("no", "", 70)]);
+        m.insert("da", vec![("dk", "", 70)]);
+        m.insert("fi", vec![("fi", "", 70)]);
+        m.insert("ja", vec![("jp", "", 70)]);
+        m.insert("ko", vec![("kr", "", 70)]);
+
+        m
+    });
+
+/// Read the active locale from `$LC_ALL`/`$LC_CTYPE`/`$LANG`, stripping any
+/// `.UTF-8`/`@modifier` suffix.
+fn current_locale() -> Option<String> {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .filter(|v| !v.is_empty() && v != "C" && v != "POSIX")
+        .map(|v| {
+            v.split(['.', '@'])
+                .next()
+                .unwrap_or(&v)
+                .to_string()
+        })
+}
+
+/// Map the current locale to a sensible default layout, preferring the
+/// highest-priority candidate whose keymap compiles. Falls back to US English
+/// only when the locale is unknown or none of its candidates compile.
+fn guess_layout_from_locale(context: &xkb::Context) -> (String, String) {
+    let Some(locale) = current_locale() else {
+        return ("us".to_string(), String::new());
+    };
+
+    // Full 5-char key first (e.g. `es_MX`), then the 2-char language prefix.
+    let key5: String = locale.chars().take(5).collect();
+    let lang: String = locale.chars().take(2).collect();
+
+    let mut candidates: Vec<(&str, &str, u8)> = Vec::new();
+    if let Some(list) = LANG_TO_LAYOUT.get(key5.as_str()) {
+        candidates.extend(list.iter().copied());
+    }
+    if let Some(list) = LANG_TO_LAYOUT.get(lang.as_str()) {
+        candidates.extend(list.iter().copied());
+    }
+    candidates.sort_by(|a, b| b.2.cmp(&a.2));
+
+    for (layout, variant, _) in candidates {
+        if keymap_compiles(context, layout, variant) {
+            tracing::debug!("Guessed layout '{}' (variant '{}') from locale '{}'", layout, variant, locale);
+            return (layout.to_string(), variant.to_string());
+        }
+    }
+
+    tracing::debug!("No locale candidate compiled for '{}', using US", locale);
+    ("us".to_string(), String::new())
+}
+
+/// Whether `new_from_names` can build a keymap for this layout/variant.
+fn keymap_compiles(context: &xkb::Context, layout: &str, variant: &str) -> bool {
+    xkb::Keymap::new_from_names(
+        context,
+        "",
+        "",
+        layout,
+        variant,
+        None,
+        xkb::KEYMAP_COMPILE_NO_FLAGS,
+    )
+    .is_some()
+}
+
 fn parse_layout_name(name: &str) -> (&str, &str) {
     if let Some(&(layout, variant)) = LAYOUT_NAME_MAP.get(name) {
         return (layout, variant);
@@ -144,6 +262,18 @@ fn parse_layout_name(name: &str) -> (&str, &str) {
     }
 
     let trimmed = name.trim();
+
+    // Raw xkb identifiers such as GNOME's `us+dvorak` or `de`: the pieces are
+    // already layout/variant codes, so pass them straight through.
+    if let Some((layout, variant)) = trimmed.split_once('+') {
+        if !layout.is_empty()
+            && layout.chars().all(|c| c.is_ascii_lowercase())
+            && variant.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+        {
+            return (layout, variant);
+        }
+    }
+
     if trimmed.len() <= 5 && trimmed.chars().all(|c| c.is_ascii_lowercase()) {
         return (trimmed, "");
     }
@@ -157,20 +287,461 @@ pub struct XkbState {
     keymap: xkb::Keymap,
     state: xkb::State,
     layout_name: String,
+    layout: String,
+    variant: String,
+    groups: Vec<(String, String)>,
+    compose: Option<ComposeLayer>,
+}
+
+/// Result of feeding a keysym through the compose layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compose {
+    /// No compose table, or the keysym neither starts nor continues a sequence;
+    /// the caller should fall back to the plain `key_get_utf8` translation.
+    Pass,
+    /// A compose sequence is in progress; emit nothing until it resolves.
+    Composing,
+    /// A sequence completed, yielding this (possibly multi-codepoint) string.
+    Composed(String),
+}
+
+/// A `libxkbcommon` compose state seeded from the current locale's Compose file,
+/// used to fold dead-key and multi-key sequences (e.g. `´` then `e` → `é`) into
+/// a single emitted grapheme.
+struct ComposeLayer {
+    state: xkb::compose::State,
+}
+
+impl ComposeLayer {
+    /// Build the compose state for the active locale, returning `None` when the
+    /// locale has no Compose file or it fails to compile (the overlay then just
+    /// falls back to direct key translation).
+    fn new(context: &xkb::Context) -> Option<Self> {
+        let locale = compose_locale();
+        let table = xkb::compose::Table::new_from_locale(
+            context,
+            &locale,
+            xkb::compose::COMPILE_NO_FLAGS,
+        )
+        .ok()?;
+        let state = xkb::compose::State::new(&table, xkb::compose::STATE_NO_FLAGS);
+        Some(Self { state })
+    }
+}
+
+/// The locale string to seed the compose table with, read from the same
+/// environment variables as [`current_locale`] but preserving the encoding
+/// suffix (`.UTF-8`) that `xkb_compose_table_new_from_locale` expects.
+fn compose_locale() -> String {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "C".to_string())
+}
+
+/// Split a combined source string such as `"us,ru,de"` or `"us,ru"` with
+/// per-group variants (`",phonetic"`) into `(layout, variant)` pairs. Each comma
+/// field may itself be a raw `layout+variant` id.
+pub fn parse_combined_layouts(combined: &str) -> Vec<(String, String)> {
+    combined
+        .split(',')
+        .map(|field| {
+            let (layout, variant) = parse_layout_name(field.trim());
+            (layout.to_string(), variant.to_string())
+        })
+        .collect()
+}
+
+/// A compact, uppercase badge for the active layout, short enough to fit in a
+/// status pill. Variants that share a base layout get a distinct tag
+/// (`us`+`dvorak` → `DV`); everything else falls back to the uppercased
+/// layout code (`us` → `US`, `latam` → `LATAM`).
+pub fn layout_indicator(layout: &str, variant: &str) -> String {
+    match (layout, variant) {
+        ("us", "dvorak") => "DV".to_string(),
+        ("us", "intl") => "INTL".to_string(),
+        ("us", "altgr-intl") | ("us", "alt-intl") => "EXTD".to_string(),
+        ("us", "colemak") => "CO".to_string(),
+        ("de", "neo") => "NEO".to_string(),
+        ("es", "cat") => "CAT".to_string(),
+        _ => layout.to_uppercase(),
+    }
+}
+
+/// A full XKB keymap description: physical keyboard model, layout, variant, and
+/// the list of layout options (`caps:swapescape`, `grp:alt_shift_toggle`, …).
+/// These map directly onto the arguments of `xkb::Keymap::new_from_names`.
+#[derive(Debug, Clone, Default)]
+pub struct XkbConfig {
+    pub model: String,
+    pub layout: String,
+    pub variant: String,
+    pub options: Vec<String>,
+}
+
+impl XkbConfig {
+    /// Pull the model, layout, variant, and options out of the application
+    /// config, resolving the layout name through [`parse_layout_name`] so both
+    /// display names and raw xkb ids are accepted.
+    #[must_use]
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        let (layout, variant) = if config.xkb_layout.is_empty() {
+            ("us".to_string(), String::new())
+        } else {
+            let (l, v) = parse_layout_name(&config.xkb_layout);
+            // An explicit variant in the config overrides the parsed one.
+            let variant = if config.xkb_variant.is_empty() {
+                v.to_string()
+            } else {
+                config.xkb_variant.clone()
+            };
+            (l.to_string(), variant)
+        };
+
+        Self {
+            model: config.xkb_model.clone(),
+            layout,
+            variant,
+            options: config.xkb_options.clone(),
+        }
+    }
 }
 
 impl XkbState {
+    /// Build the default keyboard state, preferring the system's X11/`localed`
+    /// keyboard configuration (`/etc/default/keyboard`, `/etc/vconsole.conf`)
+    /// over the locale-derived guess in [`Self::from_layout_name`].
     pub fn new() -> Option<Self> {
+        if let Some(state) = super::localed::X11Context::from_system().and_then(|ctx| ctx.to_xkb_state()) {
+            return Some(state);
+        }
+
         Self::from_layout_name(None)
     }
 
+    /// Build a keymap from a full [`XkbConfig`], honoring the keyboard model and
+    /// layout options that [`Self::from_layout_name`] leaves at their defaults.
+    pub fn from_config(config: &XkbConfig) -> Option<Self> {
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+
+        let options = if config.options.is_empty() {
+            None
+        } else {
+            Some(config.options.join(","))
+        };
+
+        tracing::debug!(
+            "Creating XKB state from config: model='{}', layout='{}', variant='{}', options={:?}",
+            config.model,
+            config.layout,
+            config.variant,
+            config.options
+        );
+
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            "",
+            &config.model,
+            &config.layout,
+            &config.variant,
+            options,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )?;
+
+        let state = xkb::State::new(&keymap);
+        let compose = ComposeLayer::new(&context);
+
+        Some(Self {
+            context,
+            keymap,
+            state,
+            layout_name: config.layout.clone(),
+            layout: config.layout.clone(),
+            variant: config.variant.clone(),
+            groups: vec![(config.layout.clone(), config.variant.clone())],
+            compose,
+        })
+    }
+
+    /// Build a single keymap holding several layouts (`us,ru`) that can be
+    /// switched between with [`Self::set_group`] without recompiling. Variants
+    /// are matched positionally, so `[("us", ""), ("ru", "phonetic")]` compiles
+    /// layouts `us,ru` with variants `,phonetic`.
+    pub fn from_layouts(layouts: &[(&str, &str)]) -> Option<Self> {
+        if layouts.is_empty() {
+            return Self::from_layout_name(None);
+        }
+
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+
+        let layout = layouts
+            .iter()
+            .map(|(l, _)| *l)
+            .collect::<Vec<_>>()
+            .join(",");
+        let variant = layouts
+            .iter()
+            .map(|(_, v)| *v)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        tracing::debug!("Creating multi-group XKB state: layouts='{}', variants='{}'", layout, variant);
+
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            "",
+            "",
+            &layout,
+            &variant,
+            None,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )?;
+
+        let state = xkb::State::new(&keymap);
+        let groups: Vec<(String, String)> = layouts
+            .iter()
+            .map(|(l, v)| (l.to_string(), v.to_string()))
+            .collect();
+        let (first_layout, first_variant) = groups[0].clone();
+        let compose = ComposeLayer::new(&context);
+
+        Some(Self {
+            context,
+            keymap,
+            state,
+            layout_name: layout,
+            layout: first_layout,
+            variant: first_variant,
+            groups,
+            compose,
+        })
+    }
+
+    /// Build a multi-group keymap from a combined source string (`"us,ru,de"`).
+    pub fn from_combined(combined: &str) -> Option<Self> {
+        let pairs = parse_combined_layouts(combined);
+        let refs: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|(l, v)| (l.as_str(), v.as_str()))
+            .collect();
+        Self::from_layouts(&refs)
+    }
+
+    /// Activate layout group `index`, updating key translation without
+    /// rebuilding the keymap. Out-of-range indices are ignored.
+    pub fn set_group(&mut self, index: u32) {
+        if index >= self.keymap.num_layouts() {
+            return;
+        }
+
+        self.state.update_mask(0, 0, 0, 0, 0, index);
+
+        if let Some((layout, variant)) = self.groups.get(index as usize) {
+            self.layout = layout.clone();
+            self.variant = variant.clone();
+        }
+    }
+
+    /// Index of the currently active layout group.
+    #[must_use]
+    pub fn current_group(&self) -> u32 {
+        self.state.serialize_layout(xkb::STATE_LAYOUT_EFFECTIVE)
+    }
+
+    /// Number of layout groups compiled into the current keymap.
+    #[must_use]
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Compile a single keymap holding every layout in `layouts` (display names
+    /// or raw xkb ids) plus a group-toggle option such as
+    /// `grp:alt_shift_toggle`. Unlike [`Self::set_group`], the active group then
+    /// advances on its own as the toggle keys are fed through
+    /// [`Self::update_key`], and [`Self::key_get_utf8`] resolves against
+    /// whichever group is live.
+    pub fn set_layouts(&mut self, layouts: &[&str], options: Option<&str>) -> bool {
+        if layouts.is_empty() {
+            return false;
+        }
+
+        let pairs: Vec<(String, String)> = layouts
+            .iter()
+            .map(|name| {
+                let (l, v) = parse_layout_name(name);
+                (l.to_string(), v.to_string())
+            })
+            .collect();
+
+        let layout = pairs
+            .iter()
+            .map(|(l, _)| l.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+        let variant = pairs
+            .iter()
+            .map(|(_, v)| v.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        tracing::debug!(
+            "Setting multi-group layouts: layout='{}', variant='{}', options={:?}",
+            layout,
+            variant,
+            options
+        );
+
+        let keymap = match xkb::Keymap::new_from_names(
+            &self.context,
+            "",
+            "",
+            &layout,
+            &variant,
+            options.map(str::to_string),
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        ) {
+            Some(k) => k,
+            None => {
+                tracing::warn!("Failed to compile multi-group keymap for '{}'", layout);
+                return false;
+            }
+        };
+
+        self.keymap = keymap;
+        self.state = xkb::State::new(&self.keymap);
+        self.layout_name = layout;
+        let (first_layout, first_variant) = pairs[0].clone();
+        self.layout = first_layout;
+        self.variant = first_variant;
+        self.groups = pairs;
+        true
+    }
+
+    /// Index of the layout group currently active in the state, resolved via
+    /// `xkb_state_layout_index_is_active` over the effective component.
+    #[must_use]
+    pub fn active_layout_index(&self) -> u32 {
+        for idx in 0..self.keymap.num_layouts() {
+            if self
+                .state
+                .layout_index_is_active(idx, xkb::STATE_LAYOUT_EFFECTIVE)
+            {
+                return idx;
+            }
+        }
+
+        0
+    }
+
+    /// The keymap's own name for the active layout group (e.g. `Russian`), as
+    /// reported by `xkb_keymap_layout_get_name`.
+    #[must_use]
+    pub fn active_layout_name(&self) -> Option<String> {
+        let name = self.keymap.layout_get_name(self.active_layout_index());
+
+        if name.is_empty() {
+            None
+        } else {
+            Some(name.to_string())
+        }
+    }
+
+    /// Clear depressed and latched modifier state, leaving the locked layout
+    /// group untouched so a group switched via the toggle survives a reset.
+    pub fn reset_modifiers(&mut self) {
+        let locked_mods = self.state.serialize_mods(xkb::STATE_MODS_LOCKED);
+        let locked_layout = self.state.serialize_layout(xkb::STATE_LAYOUT_LOCKED);
+        self.state.update_mask(0, 0, locked_mods, 0, 0, locked_layout);
+    }
+
+    /// Rebuild the keymap from a `wl_keyboard.keymap` file descriptor. The
+    /// descriptor is `mmap`ped read-only, compiled with
+    /// `xkb_keymap_new_from_string`, and unmapped immediately; the caller still
+    /// owns `fd` and is responsible for closing it. The current modifier and
+    /// layout depression is carried over to the new state so a keymap swap
+    /// mid-chord doesn't drop held modifiers.
+    pub fn load_from_fd(&mut self, fd: RawFd, size: u32) -> bool {
+        let Some(len) = NonZeroUsize::new(size as usize) else {
+            return false;
+        };
+
+        let borrowed = unsafe { BorrowedFd::borrow_raw(fd) };
+
+        let ptr = match unsafe {
+            mmap(
+                None,
+                len,
+                ProtFlags::PROT_READ,
+                MapFlags::MAP_SHARED,
+                borrowed,
+                0,
+            )
+        } {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::warn!("Failed to mmap keymap fd: {}", e);
+                return false;
+            }
+        };
+
+        let bytes = unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u8>(), size as usize) };
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        let keymap_str = std::str::from_utf8(&bytes[..end]).map(str::to_string);
+
+        let keymap = keymap_str.ok().and_then(|s| {
+            xkb::Keymap::new_from_string(
+                &self.context,
+                s,
+                xkb::KEYMAP_FORMAT_TEXT_V1,
+                xkb::KEYMAP_COMPILE_NO_FLAGS,
+            )
+        });
+
+        unsafe {
+            let _ = munmap(ptr, size as usize);
+        }
+
+        let Some(keymap) = keymap else {
+            tracing::warn!("Failed to compile keymap from fd");
+            return false;
+        };
+
+        // Snapshot the live state so held modifiers and the active group carry
+        // across the keymap swap.
+        let depressed_mods = self.state.serialize_mods(xkb::STATE_MODS_DEPRESSED);
+        let latched_mods = self.state.serialize_mods(xkb::STATE_MODS_LATCHED);
+        let locked_mods = self.state.serialize_mods(xkb::STATE_MODS_LOCKED);
+        let depressed_layout = self.state.serialize_layout(xkb::STATE_LAYOUT_DEPRESSED);
+        let latched_layout = self.state.serialize_layout(xkb::STATE_LAYOUT_LATCHED);
+        let locked_layout = self.state.serialize_layout(xkb::STATE_LAYOUT_LOCKED);
+
+        self.keymap = keymap;
+        self.state = xkb::State::new(&self.keymap);
+        self.state.update_mask(
+            depressed_mods,
+            latched_mods,
+            locked_mods,
+            depressed_layout,
+            latched_layout,
+            locked_layout,
+        );
+        self.layout_name = "wayland".to_string();
+
+        true
+    }
+
     pub fn from_layout_name(name: Option<&str>) -> Option<Self> {
         let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
 
-        let (layout, variant) = match name {
-            Some(n) => parse_layout_name(n),
-            None => ("", ""),
+        let (layout, variant): (String, String) = match name {
+            Some(n) => {
+                let (l, v) = parse_layout_name(n);
+                (l.to_string(), v.to_string())
+            }
+            None => guess_layout_from_locale(&context),
         };
+        let (layout, variant) = (layout.as_str(), variant.as_str());
 
         let layout_name = name.unwrap_or("default").to_string();
 
@@ -212,12 +783,17 @@ impl XkbState {
         };
 
         let state = xkb::State::new(&keymap);
+        let compose = ComposeLayer::new(&context);
 
         Some(Self {
             context,
             keymap,
             state,
             layout_name,
+            layout: layout.to_string(),
+            variant: variant.to_string(),
+            groups: vec![(layout.to_string(), variant.to_string())],
+            compose,
         })
     }
 
@@ -226,6 +802,15 @@ impl XkbState {
         &self.layout_name
     }
 
+    /// Short uppercase badge for the active layout, e.g. `DV` for US-Dvorak.
+    /// Tracks the live group so toggling between layouts updates the badge.
+    pub fn indicator(&self) -> String {
+        match self.groups.get(self.active_layout_index() as usize) {
+            Some((layout, variant)) => layout_indicator(layout, variant),
+            None => layout_indicator(&self.layout, &self.variant),
+        }
+    }
+
     pub fn set_layout(&mut self, name: &str) -> bool {
         let (layout, variant) = parse_layout_name(name);
 
@@ -255,6 +840,9 @@ impl XkbState {
         self.keymap = keymap;
         self.state = xkb::State::new(&self.keymap);
         self.layout_name = name.to_string();
+        self.layout = layout.to_string();
+        self.variant = variant.to_string();
+        self.groups = vec![(layout.to_string(), variant.to_string())];
         true
     }
 
@@ -273,6 +861,39 @@ impl XkbState {
         self.state.update_key(keycode, direction);
     }
 
+    /// Feed a key's keysym through the locale compose table. Dead keys and the
+    /// start of a multi-key sequence return [`Compose::Composing`] (emit
+    /// nothing yet); a finished sequence returns [`Compose::Composed`] with the
+    /// full string; anything else — including a cancelled sequence — returns
+    /// [`Compose::Pass`] so the caller falls back to [`Self::key_get_utf8`].
+    pub fn compose_feed(&mut self, key: Key) -> Compose {
+        let keycode = Self::key_to_keycode(key);
+        let sym = self.state.key_get_one_sym(keycode);
+
+        let Some(compose) = self.compose.as_mut() else {
+            return Compose::Pass;
+        };
+        compose.state.feed(sym);
+
+        match compose.state.status() {
+            xkb::compose::Status::Composing => Compose::Composing,
+            xkb::compose::Status::Composed => {
+                let utf8 = compose.state.utf8().unwrap_or_default();
+                compose.state.reset();
+                if utf8.is_empty() {
+                    Compose::Pass
+                } else {
+                    Compose::Composed(utf8)
+                }
+            }
+            xkb::compose::Status::Cancelled => {
+                compose.state.reset();
+                Compose::Pass
+            }
+            xkb::compose::Status::Nothing => Compose::Pass,
+        }
+    }
+
     pub fn key_get_utf8(&self, key: Key) -> Option<String> {
         let keycode = Self::key_to_keycode(key);
         let utf8 = self.state.key_get_utf8(keycode);
@@ -407,6 +1028,115 @@ mod tests {
         assert_eq!(result, Some("#".to_string()));
     }
 
+    #[test]
+    fn test_locale_candidates_sorted_by_priority() {
+        // The Mexican Spanish locale should prefer the Latin American layout.
+        let list = LANG_TO_LAYOUT.get("es_MX").unwrap();
+        let top = list.iter().max_by_key(|c| c.2).unwrap();
+        assert_eq!(top.0, "latam");
+    }
+
+    #[test]
+    fn test_parse_combined_layouts() {
+        let pairs = parse_combined_layouts("us,ru,de");
+        assert_eq!(
+            pairs,
+            vec![
+                ("us".to_string(), String::new()),
+                ("ru".to_string(), String::new()),
+                ("de".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multi_group_switch() {
+        let mut state = XkbState::from_combined("us,ru").unwrap();
+        assert_eq!(state.current_group(), 0);
+        assert_eq!(state.key_get_utf8(Key::KEY_A), Some("a".to_string()));
+
+        state.set_group(1);
+        assert_eq!(state.current_group(), 1);
+        assert_eq!(state.indicator(), "RU");
+
+        // Cyrillic output from the second group without recompiling the keymap.
+        assert_eq!(state.key_get_utf8(Key::KEY_A), Some("ф".to_string()));
+
+        state.set_group(0);
+        assert_eq!(state.key_get_utf8(Key::KEY_A), Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_xkb_from_config_with_options() {
+        let config = XkbConfig {
+            model: "pc105".to_string(),
+            layout: "us".to_string(),
+            variant: String::new(),
+            options: vec!["caps:swapescape".to_string()],
+        };
+
+        let state = XkbState::from_config(&config).unwrap();
+        assert_eq!(state.indicator(), "US");
+        assert_eq!(state.key_get_utf8(Key::KEY_A), Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_parse_raw_xkb_identifier() {
+        assert_eq!(parse_layout_name("us+dvorak"), ("us", "dvorak"));
+        assert_eq!(parse_layout_name("de"), ("de", ""));
+    }
+
+    #[test]
+    fn test_layout_indicator_badges() {
+        assert_eq!(layout_indicator("us", "dvorak"), "DV");
+        assert_eq!(layout_indicator("us", "intl"), "INTL");
+        assert_eq!(layout_indicator("us", "altgr-intl"), "EXTD");
+        assert_eq!(layout_indicator("us", "colemak"), "CO");
+        assert_eq!(layout_indicator("de", "neo"), "NEO");
+        assert_eq!(layout_indicator("es", "cat"), "CAT");
+        assert_eq!(layout_indicator("us", ""), "US");
+        assert_eq!(layout_indicator("de", ""), "DE");
+    }
+
+    #[test]
+    fn test_unknown_layout_does_not_compile() {
+        // The locale guesser relies on rejecting layouts xkb cannot build.
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        assert!(!keymap_compiles(&context, "nonexistent-layout", ""));
+    }
+
+    #[test]
+    fn test_set_layouts_with_toggle_option() {
+        let mut state = XkbState::new().unwrap();
+        assert!(state.set_layouts(&["us", "ru"], Some("grp:alt_shift_toggle")));
+        assert_eq!(state.group_count(), 2);
+        assert_eq!(state.active_layout_index(), 0);
+        assert_eq!(state.key_get_utf8(Key::KEY_A), Some("a".to_string()));
+        assert!(state.active_layout_name().is_some());
+    }
+
+    #[test]
+    fn test_reset_modifiers_preserves_locked_group() {
+        let mut state = XkbState::from_combined("us,ru").unwrap();
+        state.set_group(1);
+        state.update_key(Key::KEY_LEFTSHIFT, true);
+        assert!(state.is_shift_active());
+
+        state.reset_modifiers();
+        assert!(!state.is_shift_active());
+        // The group locked via set_group survives a modifier reset.
+        assert_eq!(state.current_group(), 1);
+    }
+
+    #[test]
+    fn test_compose_feed_passes_plain_keys() {
+        let mut state = XkbState::from_layout_name(Some("English (US)")).unwrap();
+        // A plain letter neither starts nor continues a compose sequence, so it
+        // passes through to the normal translation path.
+        assert_eq!(state.compose_feed(Key::KEY_A), Compose::Pass);
+        assert_eq!(state.key_get_utf8(Key::KEY_A), Some("a".to_string()));
+    }
+
     #[test]
     fn test_xkb_layout_switch() {
         let mut state = XkbState::from_layout_name(Some("English (US)")).unwrap();
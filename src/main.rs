@@ -1,4 +1,5 @@
 mod app;
+mod compositor;
 mod config;
 mod input;
 mod tray;
@@ -28,6 +29,10 @@ fn main() -> Result<()> {
 
     let (tray_tx, tray_rx) = mpsc::channel();
 
+    let mut app = app::App::new(config)?;
+    let command_tx = app.command_sender();
+    let app_events = app.event_receiver();
+
     thread::spawn(move || match tray::start_tray() {
         Ok((action_receiver, handle)) => {
             debug!("Tray started successfully");
@@ -50,6 +55,15 @@ fn main() -> Result<()> {
                         break;
                     }
                 }
+
+                // Mirror any mode/pause changes the app made on its own (via the
+                // launcher or a keybind) back into the tray icon.
+                while let Ok(event) = app_events.try_recv() {
+                    match event {
+                        app::AppEvent::ModeChanged(mode) => handle.set_mode(mode),
+                        app::AppEvent::PauseChanged(paused) => handle.set_paused(paused),
+                    }
+                }
             }
 
             drop(handle);
@@ -59,31 +73,33 @@ fn main() -> Result<()> {
         }
     });
 
-    let app = app::App::new(config)?;
-
     glib::timeout_add_local(Duration::from_millis(100), move || {
         while let Ok(action) = tray_rx.try_recv() {
-            match action {
+            let command = match action {
                 tray::TrayAction::Quit => {
                     debug!("Quit action received from tray");
 
                     std::process::exit(0);
                 }
-                tray::TrayAction::ShowLauncher => {
-                    debug!("Show launcher action from tray");
-                }
+                tray::TrayAction::ShowLauncher => app::Command::ShowLauncher,
                 tray::TrayAction::KeystrokeMode => {
-                    debug!("Keystroke mode action from tray");
+                    app::Command::SetMode(ui::DisplayMode::Keystroke)
                 }
                 tray::TrayAction::BubbleMode => {
-                    debug!("Bubble mode action from tray");
+                    app::Command::SetMode(ui::DisplayMode::Bubble)
                 }
-                tray::TrayAction::OpenSettings => {
-                    debug!("Settings action from tray");
-                }
-                tray::TrayAction::TogglePause => {
-                    debug!("Toggle pause action from tray");
+                tray::TrayAction::OpenSettings => app::Command::OpenSettings,
+                tray::TrayAction::TogglePause => app::Command::TogglePause,
+                tray::TrayAction::SwitchLayout(idx) => {
+                    // The tray already drove the compositor switch; nothing for
+                    // the overlay app to do beyond noting it.
+                    debug!("Layout switch to index {} requested from tray", idx);
+                    continue;
                 }
+            };
+
+            if let Err(e) = command_tx.try_send(command) {
+                warn!("Failed to forward command to app: {}", e);
             }
         }
         glib::ControlFlow::Continue
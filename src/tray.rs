@@ -1,6 +1,13 @@
-use ksni::{self, menu::StandardItem, Icon, MenuItem, Tray, TrayService};
+use crate::compositor::{self, CompositorClient, KeyboardLayouts, LayoutEvent};
+use crate::ui::DisplayMode;
+use ksni::{
+    self,
+    menu::{CheckmarkItem, StandardItem, SubMenu},
+    Icon, MenuItem, Tray, TrayService,
+};
 use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Mutex};
+use std::thread;
 use tracing::{debug, error, info};
 
 #[derive(Debug, Clone)]
@@ -15,22 +22,87 @@ pub enum TrayAction {
 
     TogglePause,
 
+    SwitchLayout(usize),
+
     Quit,
 }
 
 pub struct TrayState {
     pub paused: bool,
+
+    /// Active display mode, so the icon can render a mode-specific glyph.
+    pub mode: DisplayMode,
+
+    /// Names of the available keyboard layouts, mirrored from the compositor.
+    pub layouts: Vec<String>,
+
+    /// Index into [`layouts`](Self::layouts) of the active one.
+    pub current_layout: usize,
 }
 
 impl Default for TrayState {
     fn default() -> Self {
-        Self { paused: false }
+        Self {
+            paused: false,
+            mode: DisplayMode::Keystroke,
+            layouts: Vec::new(),
+            current_layout: 0,
+        }
+    }
+}
+
+/// Side length, in pixels, of the generated tray icon.
+const ICON_SIZE: i32 = 22;
+
+/// Render an ARGB32 tray icon that reflects the current state: a teal badge for
+/// Keystroke mode and an amber one for Bubble mode, drawn as a filled square or
+/// circle respectively, dimmed to roughly a third opacity while paused. The
+/// bytes are ARGB in network (big-endian) order, as the StatusNotifierItem
+/// pixmap protocol expects.
+fn render_icon(mode: DisplayMode, paused: bool) -> Icon {
+    let size = ICON_SIZE;
+    let (r, g, b) = match mode {
+        DisplayMode::Keystroke => (0x26u8, 0xa6u8, 0x9au8),
+        DisplayMode::Bubble => (0xf0u8, 0xa3u8, 0x0au8),
+    };
+    let alpha: u8 = if paused { 0x55 } else { 0xff };
+
+    let center = (size as f32 - 1.0) / 2.0;
+    let radius = center;
+
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let inside = match mode {
+                DisplayMode::Keystroke => true,
+                DisplayMode::Bubble => {
+                    let dx = x as f32 - center;
+                    let dy = y as f32 - center;
+                    dx * dx + dy * dy <= radius * radius
+                }
+            };
+
+            if inside {
+                data.extend_from_slice(&[alpha, r, g, b]);
+            } else {
+                data.extend_from_slice(&[0, 0, 0, 0]);
+            }
+        }
+    }
+
+    Icon {
+        width: size,
+        height: size,
+        data,
     }
 }
 
 struct KeystrokeTray {
     action_sender: Sender<TrayAction>,
     state: Arc<Mutex<TrayState>>,
+
+    /// Compositor client used to drive layout switches from the menu.
+    client: Option<Box<dyn CompositorClient>>,
 }
 
 impl Tray for KeystrokeTray {
@@ -47,7 +119,13 @@ impl Tray for KeystrokeTray {
     }
 
     fn icon_pixmap(&self) -> Vec<Icon> {
-        Vec::new()
+        let (mode, paused) = self
+            .state
+            .lock()
+            .map(|s| (s.mode, s.paused))
+            .unwrap_or((DisplayMode::Keystroke, false));
+
+        vec![render_icon(mode, paused)]
     }
 
     fn tool_tip(&self) -> ksni::ToolTip {
@@ -71,13 +149,18 @@ impl Tray for KeystrokeTray {
     fn menu(&self) -> Vec<MenuItem<Self>> {
         let state = self.state.lock().unwrap();
         let pause_label = if state.paused { "Resume" } else { "Pause" };
+        let layouts = state.layouts.clone();
+        let current_layout = state.current_layout;
         drop(state);
 
-        vec![
+        let mut items = vec![
             MenuItem::Standard(StandardItem {
                 label: "Keystroke Mode".to_string(),
                 activate: Box::new(|tray: &mut Self| {
                     debug!("Tray: Keystroke mode selected");
+                    if let Ok(mut state) = tray.state.lock() {
+                        state.mode = DisplayMode::Keystroke;
+                    }
                     let _ = tray.action_sender.send(TrayAction::KeystrokeMode);
                 }),
                 ..Default::default()
@@ -86,10 +169,46 @@ impl Tray for KeystrokeTray {
                 label: "Bubble Mode".to_string(),
                 activate: Box::new(|tray: &mut Self| {
                     debug!("Tray: Bubble mode selected");
+                    if let Ok(mut state) = tray.state.lock() {
+                        state.mode = DisplayMode::Bubble;
+                    }
                     let _ = tray.action_sender.send(TrayAction::BubbleMode);
                 }),
                 ..Default::default()
             }),
+        ];
+
+        // A dynamic submenu listing every layout, the active one checked.
+        if !layouts.is_empty() {
+            let layout_items = layouts
+                .into_iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    MenuItem::Checkmark(CheckmarkItem {
+                        label: name,
+                        checked: index == current_layout,
+                        activate: Box::new(move |tray: &mut Self| {
+                            debug!("Tray: switch to layout {}", index);
+                            if let Some(client) = &tray.client {
+                                if let Err(e) = client.set_layout(index) {
+                                    error!("Failed to switch layout: {}", e);
+                                }
+                            }
+                            let _ = tray.action_sender.send(TrayAction::SwitchLayout(index));
+                        }),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+
+            items.push(MenuItem::SubMenu(SubMenu {
+                label: "Keyboard Layout".to_string(),
+                submenu: layout_items,
+                ..Default::default()
+            }));
+        }
+
+        items.extend([
             MenuItem::Separator,
             MenuItem::Standard(StandardItem {
                 label: "Settings".to_string(),
@@ -116,7 +235,9 @@ impl Tray for KeystrokeTray {
                 }),
                 ..Default::default()
             }),
-        ]
+        ]);
+
+        items
     }
 }
 
@@ -127,7 +248,6 @@ pub struct TrayHandle {
 }
 
 impl TrayHandle {
-    #[allow(dead_code)]
     pub fn set_paused(&self, paused: bool) {
         if let Ok(mut state) = self.state.lock() {
             state.paused = paused;
@@ -140,20 +260,85 @@ impl TrayHandle {
     pub fn is_paused(&self) -> bool {
         self.state.lock().map(|s| s.paused).unwrap_or(false)
     }
+
+    /// Record the active display mode and redraw the icon to match.
+    pub fn set_mode(&self, mode: DisplayMode) {
+        if let Ok(mut state) = self.state.lock() {
+            state.mode = mode;
+        }
+
+        self.service.handle().update(|_| {});
+    }
+
+    /// Mirror a fresh layout snapshot into the menu and redraw it.
+    #[allow(dead_code)]
+    pub fn set_layouts(&self, layouts: &KeyboardLayouts) {
+        if let Ok(mut state) = self.state.lock() {
+            state.layouts = layouts.names.clone();
+            state.current_layout = layouts.current_idx;
+        }
+
+        self.service.handle().update(|_| {});
+    }
 }
 
 pub fn start_tray() -> anyhow::Result<(mpsc::Receiver<TrayAction>, TrayHandle)> {
     let (sender, receiver) = mpsc::channel();
     let state = Arc::new(Mutex::new(TrayState::default()));
 
+    // Seed the menu with the current layouts and keep a client around so the
+    // submenu can drive switches. Both are best-effort: on a compositor without
+    // a layout backend the submenu simply stays hidden.
+    let compositor = compositor::detect();
+    let client = compositor::create_client(compositor);
+
+    if let Some(client) = &client {
+        if let Ok(layouts) = client.get_keyboard_layouts() {
+            if let Ok(mut state) = state.lock() {
+                state.layouts = layouts.names;
+                state.current_layout = layouts.current_idx;
+            }
+        }
+    }
+
     let tray = KeystrokeTray {
         action_sender: sender,
         state: Arc::clone(&state),
+        client,
     };
 
     let service = TrayService::new(tray);
     let handle = TrayHandle { service, state };
 
+    // Follow live layout changes so the checkmark tracks external switches.
+    if compositor.supports_layout_events() {
+        if let Some(watcher) = compositor::create_client(compositor) {
+            let state = Arc::clone(&handle.state);
+            let tray_handle = handle.service.handle();
+
+            thread::spawn(move || match watcher.watch_layout_events() {
+                Ok(events) => {
+                    for event in events {
+                        if let Ok(mut state) = state.lock() {
+                            match event {
+                                LayoutEvent::LayoutSwitched { index, .. } => {
+                                    state.current_layout = index;
+                                }
+                                LayoutEvent::LayoutsChanged { layouts } => {
+                                    state.layouts = layouts.names;
+                                    state.current_layout = layouts.current_idx;
+                                }
+                            }
+                        }
+
+                        tray_handle.update(|_| {});
+                    }
+                }
+                Err(e) => debug!("Tray layout watcher unavailable: {}", e),
+            });
+        }
+    }
+
     handle.service.handle().update(|_| {});
 
     info!("System tray started");
@@ -1,4 +1,8 @@
-use crate::input::{KeyDisplay, XkbState};
+use crate::config::Config;
+use crate::input::{
+    is_ignored_key, key_to_display_name, normalize_modifier, Compose, KeyDisplay, PointerButton,
+    PointerEvent, XkbConfig, XkbState,
+};
 use evdev::Key;
 use gtk4::prelude::*;
 use gtk4::{Box as GtkBox, Label, Orientation};
@@ -14,6 +18,7 @@ struct ModifierState {
     ctrl: bool,
     alt: bool,
     super_key: bool,
+    shift: bool,
 }
 
 impl ModifierState {
@@ -22,6 +27,7 @@ impl ModifierState {
             Key::KEY_LEFTCTRL | Key::KEY_RIGHTCTRL => self.ctrl = pressed,
             Key::KEY_LEFTALT | Key::KEY_RIGHTALT => self.alt = pressed,
             Key::KEY_LEFTMETA | Key::KEY_RIGHTMETA => self.super_key = pressed,
+            Key::KEY_LEFTSHIFT | Key::KEY_RIGHTSHIFT => self.shift = pressed,
             _ => {}
         }
     }
@@ -29,6 +35,46 @@ impl ModifierState {
     fn has_command_modifier(&self) -> bool {
         self.ctrl || self.alt || self.super_key
     }
+
+    /// Display name for a shortcut chord: the held modifiers as their
+    /// `KEY_NAMES` glyphs followed by `key`'s display name, e.g. `󰘴+T`.
+    /// Left/right variants are collapsed through [`normalize_modifier`] so the
+    /// same chord renders once no matter which physical key was used, and Shift
+    /// is only shown alongside another command modifier (a bare `Shift+T` is
+    /// just an uppercase letter the caller handles elsewhere).
+    fn chord_label(&self, key: Key) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if self.ctrl {
+            parts.push(key_to_display_name(normalize_modifier(Key::KEY_LEFTCTRL)));
+        }
+        if self.alt {
+            parts.push(key_to_display_name(normalize_modifier(Key::KEY_LEFTALT)));
+        }
+        if self.super_key {
+            parts.push(key_to_display_name(normalize_modifier(Key::KEY_LEFTMETA)));
+        }
+        if self.shift {
+            parts.push(key_to_display_name(normalize_modifier(Key::KEY_LEFTSHIFT)));
+        }
+        parts.push(key_to_display_name(key));
+        parts.join("+")
+    }
+
+    /// The held command modifiers as their Nerd Font glyphs, ordered
+    /// Ctrl/Alt/Super, for composing labels like `󰘴+Click`.
+    fn command_glyphs(&self) -> Vec<&'static str> {
+        let mut glyphs = Vec::new();
+        if self.ctrl {
+            glyphs.push("󰘴");
+        }
+        if self.alt {
+            glyphs.push("󰘵");
+        }
+        if self.super_key {
+            glyphs.push("󰖳");
+        }
+        glyphs
+    }
 }
 
 struct ChatBubble {
@@ -61,6 +107,12 @@ impl ChatBubble {
         self.last_modified = Instant::now();
     }
 
+    fn append_str(&mut self, s: &str) {
+        self.text.push_str(s);
+        self.label.set_text(&self.text);
+        self.last_modified = Instant::now();
+    }
+
     fn backspace(&mut self) {
         self.text.pop();
         self.label.set_text(&self.text);
@@ -94,48 +146,6 @@ fn is_modifier_key(key: Key) -> bool {
     )
 }
 
-fn is_ignored_key(key: Key) -> bool {
-    matches!(
-        key,
-        Key::KEY_LEFTCTRL
-            | Key::KEY_RIGHTCTRL
-            | Key::KEY_LEFTALT
-            | Key::KEY_RIGHTALT
-            | Key::KEY_LEFTMETA
-            | Key::KEY_RIGHTMETA
-            | Key::KEY_LEFTSHIFT
-            | Key::KEY_RIGHTSHIFT
-            | Key::KEY_CAPSLOCK
-            | Key::KEY_NUMLOCK
-            | Key::KEY_SCROLLLOCK
-            | Key::KEY_FN
-            | Key::KEY_ESC
-            | Key::KEY_INSERT
-            | Key::KEY_HOME
-            | Key::KEY_END
-            | Key::KEY_PAGEUP
-            | Key::KEY_PAGEDOWN
-            | Key::KEY_UP
-            | Key::KEY_DOWN
-            | Key::KEY_LEFT
-            | Key::KEY_RIGHT
-            | Key::KEY_PRINT
-            | Key::KEY_PAUSE
-            | Key::KEY_F1
-            | Key::KEY_F2
-            | Key::KEY_F3
-            | Key::KEY_F4
-            | Key::KEY_F5
-            | Key::KEY_F6
-            | Key::KEY_F7
-            | Key::KEY_F8
-            | Key::KEY_F9
-            | Key::KEY_F10
-            | Key::KEY_F11
-            | Key::KEY_F12
-    )
-}
-
 fn key_to_char(key: Key, xkb_state: &XkbState) -> Option<BubbleInput> {
     match key {
         Key::KEY_ENTER | Key::KEY_KPENTER => return Some(BubbleInput::NewLine),
@@ -166,12 +176,25 @@ fn key_to_char(key: Key, xkb_state: &XkbState) -> Option<BubbleInput> {
 #[derive(Debug)]
 enum BubbleInput {
     Char(char),
+    /// A composed grapheme (dead-key or multi-key sequence, e.g. `é`) that may
+    /// span more than one codepoint and is appended whole.
+    Str(String),
     Backspace,
     NewLine,
 }
 
+/// Glyph shown for a mouse button press.
+fn pointer_button_glyph(button: PointerButton) -> &'static str {
+    match button {
+        PointerButton::Left => "󰍽L",
+        PointerButton::Right => "󰍽R",
+        PointerButton::Middle => "󰍽M",
+    }
+}
+
 pub struct BubbleDisplayWidget {
     container: GtkBox,
+    layout_badge: Label,
     bubbles: VecDeque<ChatBubble>,
     display_duration: Duration,
     new_bubble_timeout: Duration,
@@ -182,7 +205,7 @@ pub struct BubbleDisplayWidget {
 }
 
 impl BubbleDisplayWidget {
-    pub fn new(display_timeout_ms: u64) -> Self {
+    pub fn new(display_timeout_ms: u64, config: &Config) -> Self {
         let container = GtkBox::builder()
             .orientation(Orientation::Vertical)
             .spacing(6)
@@ -192,21 +215,33 @@ impl BubbleDisplayWidget {
 
         container.add_css_class("bubble-container");
 
+        let layout_badge = Label::new(None);
+        layout_badge.add_css_class("bubble-layout");
+        layout_badge.set_halign(gtk4::Align::Start);
+        layout_badge.set_visible(false);
+        container.append(&layout_badge);
+
+        let xkb_config = XkbConfig::from_config(config);
+        let xkb_state = XkbState::from_config(&xkb_config)
+            .or_else(XkbState::new)
+            .expect("Failed to create XKB state");
+
         Self {
             container,
+            layout_badge,
             bubbles: VecDeque::new(),
             display_duration: Duration::from_millis(display_timeout_ms),
             new_bubble_timeout: Duration::from_millis(NEW_BUBBLE_TIMEOUT_MS),
             modifiers: ModifierState::default(),
             want_new_bubble: false,
-            xkb_state: XkbState::new().expect("Failed to create XKB state"),
+            xkb_state,
             pressed_keys: HashMap::new(),
         }
     }
 
     #[allow(dead_code)]
-    pub fn with_layout(display_timeout_ms: u64, layout_name: &str) -> Self {
-        let mut widget = Self::new(display_timeout_ms);
+    pub fn with_layout(display_timeout_ms: u64, config: &Config, layout_name: &str) -> Self {
+        let mut widget = Self::new(display_timeout_ms, config);
         widget.set_layout(layout_name);
         widget
     }
@@ -215,6 +250,18 @@ impl BubbleDisplayWidget {
         self.xkb_state.set_layout(layout_name);
     }
 
+    /// Rebuild the keymap from a compositor-provided `wl_keyboard.keymap`
+    /// descriptor so displayed characters track live layout changes without a
+    /// hardcoded name. The caller retains ownership of `fd`.
+    #[allow(dead_code)]
+    pub fn load_keymap_from_fd(&mut self, fd: std::os::fd::RawFd, size: u32) -> bool {
+        let loaded = self.xkb_state.load_from_fd(fd, size);
+        if loaded {
+            self.refresh_layout_badge();
+        }
+        loaded
+    }
+
     #[allow(dead_code)]
     pub fn layout_name(&self) -> &str {
         self.xkb_state.layout_name()
@@ -229,6 +276,7 @@ impl BubbleDisplayWidget {
             if !key.is_repeat {
                 self.xkb_state.update_key(key.key, true);
                 self.modifiers.update(key.key, true);
+                self.refresh_layout_badge();
             }
             return;
         }
@@ -242,12 +290,26 @@ impl BubbleDisplayWidget {
         self.modifiers.update(key.key, true);
 
         if self.modifiers.has_command_modifier() {
+            // A command chord (Ctrl/Alt/Super + key) is the keystroke a viewer
+            // most wants to see, so surface it as its own bubble instead of
+            // swallowing the character.
+            if !key.is_repeat {
+                let label = self.modifiers.chord_label(key.key);
+                self.push_chord(&label);
+            }
             return;
         }
 
-        let input = match key_to_char(key.key, &self.xkb_state) {
-            Some(input) => input,
-            None => return,
+        // Route the keysym through the compose layer first so dead keys and
+        // multi-key sequences collapse into a single grapheme; only fall back to
+        // the direct translation when compose has nothing to say.
+        let input = match self.xkb_state.compose_feed(key.key) {
+            Compose::Composing => return,
+            Compose::Composed(s) => BubbleInput::Str(s),
+            Compose::Pass => match key_to_char(key.key, &self.xkb_state) {
+                Some(input) => input,
+                None => return,
+            },
         };
 
         match input {
@@ -263,6 +325,18 @@ impl BubbleDisplayWidget {
                     bubble.append_char(c);
                 }
             }
+            BubbleInput::Str(s) => {
+                if self.want_new_bubble {
+                    self.want_new_bubble = false;
+                    if self.bubbles.back().is_some_and(|b| !b.is_empty()) {
+                        self.create_new_bubble();
+                    }
+                }
+                self.ensure_active_bubble();
+                if let Some(bubble) = self.bubbles.back_mut() {
+                    bubble.append_str(&s);
+                }
+            }
             BubbleInput::Backspace => {
                 if let Some(bubble) = self.bubbles.back_mut() {
                     if bubble.is_empty() {
@@ -290,6 +364,7 @@ impl BubbleDisplayWidget {
         if is_modifier_key(key.key) {
             self.xkb_state.update_key(key.key, false);
             self.modifiers.update(key.key, false);
+            self.refresh_layout_badge();
             return;
         }
 
@@ -307,6 +382,54 @@ impl BubbleDisplayWidget {
         }
     }
 
+    /// Render a pointer action (click or wheel motion) as its own short-lived
+    /// bubble so the overlay works as a full screencast helper. Clicks compose
+    /// with any held command modifiers, e.g. `󰘴+󰍽L` for Ctrl+Click.
+    pub fn process_pointer(&mut self, event: PointerEvent) {
+        let glyph = match event {
+            // Only the press is shown; the matching release would just be noise.
+            PointerEvent::Down(button) => pointer_button_glyph(button).to_string(),
+            PointerEvent::Up(_) => return,
+            PointerEvent::ScrollUp => "󰍽󰞕".to_string(),
+            PointerEvent::ScrollDown => "󰍽󰞒".to_string(),
+        };
+
+        let mut parts = self.modifiers.command_glyphs();
+        parts.push(glyph.as_str());
+        let label = parts.join("+");
+
+        // Start a fresh bubble so the glyph isn't appended to typed text, and
+        // flag the next character to open another one.
+        self.create_new_bubble();
+        if let Some(bubble) = self.bubbles.back_mut() {
+            bubble.append_str(&label);
+        }
+        self.want_new_bubble = true;
+    }
+
+    /// Push a shortcut chord (e.g. `󰘴+T`) as its own short-lived bubble,
+    /// tagged with the `keystroke-chord` CSS class so it can be styled apart
+    /// from typed text. The next character opens a fresh bubble.
+    fn push_chord(&mut self, label: &str) {
+        self.create_new_bubble();
+        if let Some(bubble) = self.bubbles.back_mut() {
+            bubble.widget().add_css_class("keystroke-chord");
+            bubble.append_str(label);
+        }
+        self.want_new_bubble = true;
+    }
+
+    /// Show the active layout badge when more than one group is configured,
+    /// refreshing its text whenever the group toggles.
+    fn refresh_layout_badge(&self) {
+        if self.xkb_state.group_count() > 1 {
+            self.layout_badge.set_text(&self.xkb_state.indicator());
+            self.layout_badge.set_visible(true);
+        } else {
+            self.layout_badge.set_visible(false);
+        }
+    }
+
     fn ensure_active_bubble(&mut self) {
         let need_new_bubble = if let Some(bubble) = self.bubbles.back() {
             bubble.is_stale(self.new_bubble_timeout) && !bubble.is_empty()
@@ -349,7 +472,6 @@ impl BubbleDisplayWidget {
         }
     }
 
-    #[allow(dead_code)]
     pub fn clear(&mut self) {
         while let Some(bubble) = self.bubbles.pop_front() {
             self.container.remove(bubble.widget());
@@ -5,46 +5,125 @@ use gtk4::{Box as GtkBox, Label, Orientation};
 use std::collections::{HashSet, VecDeque};
 use std::time::{Duration, Instant};
 
+/// How long the overlay stays lit after a layout switch.
+const FLASH_DURATION: Duration = Duration::from_millis(900);
+
 #[derive(Debug)]
 struct DisplayedKey {
     key: Key,
 
-    last_active: Instant,
-
     is_held: bool,
 
     label: Label,
 }
 
+/// A set of keys that were held together within the coalescing window,
+/// rendered as a single bordered box with its keys joined by `+`. Groups
+/// expire and fade as a unit rather than key-by-key.
+#[derive(Debug)]
+struct ChordGroup {
+    container: GtkBox,
+
+    keys: Vec<DisplayedKey>,
+
+    last_active: Instant,
+}
+
+impl ChordGroup {
+    fn new() -> Self {
+        let container = GtkBox::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(0)
+            .halign(gtk4::Align::Start)
+            .valign(gtk4::Align::Center)
+            .build();
+
+        container.add_css_class("keystroke-chord");
+
+        Self {
+            container,
+            keys: Vec::new(),
+            last_active: Instant::now(),
+        }
+    }
+
+    fn push(&mut self, key: &KeyDisplay) {
+        if !self.keys.is_empty() {
+            let separator = Label::new(Some("+"));
+            separator.add_css_class("keystroke-separator");
+            self.container.append(&separator);
+        }
+
+        let label = Label::new(Some(&key.display_name));
+        label.add_css_class("keystroke-key");
+
+        if is_modifier(key.key) {
+            label.add_css_class("modifier");
+        }
+
+        self.container.append(&label);
+
+        self.keys.push(DisplayedKey {
+            key: key.key,
+            is_held: true,
+            label,
+        });
+
+        self.last_active = Instant::now();
+        self.container.remove_css_class("fading");
+    }
+
+    fn is_held(&self) -> bool {
+        self.keys.iter().any(|k| k.is_held)
+    }
+}
+
 pub struct KeyDisplayWidget {
     container: GtkBox,
 
-    displayed_keys: VecDeque<DisplayedKey>,
+    layout_label: Label,
+
+    groups: VecDeque<ChordGroup>,
 
     held_keys: HashSet<Key>,
 
+    last_empty: Option<Instant>,
+
+    flash_until: Option<Instant>,
+
     max_keys: usize,
 
     display_duration: Duration,
+
+    chord_window: Duration,
 }
 
 impl KeyDisplayWidget {
-    pub fn new(max_keys: usize, display_timeout_ms: u64) -> Self {
+    pub fn new(max_keys: usize, display_timeout_ms: u64, chord_coalesce_ms: u64) -> Self {
         let container = GtkBox::builder()
             .orientation(Orientation::Horizontal)
-            .spacing(4)
+            .spacing(8)
             .halign(gtk4::Align::Start)
             .valign(gtk4::Align::Center)
             .build();
 
         container.add_css_class("keystroke-container");
 
+        let layout_label = Label::new(None);
+        layout_label.add_css_class("keystroke-layout");
+        layout_label.set_visible(false);
+        container.append(&layout_label);
+
         Self {
             container,
-            displayed_keys: VecDeque::new(),
+            layout_label,
+            groups: VecDeque::new(),
             held_keys: HashSet::new(),
+            last_empty: None,
+            flash_until: None,
             max_keys,
             display_duration: Duration::from_millis(display_timeout_ms),
+            chord_window: Duration::from_millis(chord_coalesce_ms),
         }
     }
 
@@ -53,57 +132,66 @@ impl KeyDisplayWidget {
     }
 
     pub fn add_key(&mut self, key: KeyDisplay) {
+        let was_empty = self.held_keys.is_empty();
         self.held_keys.insert(key.key);
 
-        if let Some(existing) = self.displayed_keys.iter_mut().find(|dk| dk.key == key.key) {
-            existing.last_active = Instant::now();
-            existing.is_held = true;
-            existing.label.remove_css_class("fading");
+        if let Some(group) = self
+            .groups
+            .iter_mut()
+            .find(|g| g.keys.iter().any(|k| k.key == key.key))
+        {
+            if let Some(existing) = group.keys.iter_mut().find(|k| k.key == key.key) {
+                existing.is_held = true;
+                existing.label.remove_css_class("fading");
+            }
+            group.last_active = Instant::now();
+            group.container.remove_css_class("fading");
             return;
         }
 
         self.remove_expired();
 
-        while self.displayed_keys.len() >= self.max_keys {
-            if let Some(old) = self.displayed_keys.pop_front() {
-                self.container.remove(&old.label);
+        // Attach to the current group when keys are still held, or when the
+        // held set only briefly emptied (within the coalescing window);
+        // otherwise begin a fresh group rendered side-by-side.
+        let continue_group = !self.groups.is_empty()
+            && (!was_empty
+                || self
+                    .last_empty
+                    .is_some_and(|t| t.elapsed() <= self.chord_window));
+
+        if !continue_group {
+            while self.groups.len() >= self.max_keys {
+                if let Some(old) = self.groups.pop_front() {
+                    self.container.remove(&old.container);
+                }
             }
-        }
-
-        self.cleanup_separators();
 
-        if !self.displayed_keys.is_empty() {
-            let separator = Label::new(Some("+"));
-            separator.add_css_class("keystroke-separator");
-            self.container.append(&separator);
+            let group = ChordGroup::new();
+            self.container.append(&group.container);
+            self.groups.push_back(group);
         }
 
-        let label = Label::new(Some(&key.display_name));
-        label.add_css_class("keystroke-key");
-
-        if is_modifier(key.key) {
-            label.add_css_class("modifier");
+        if let Some(group) = self.groups.back_mut() {
+            group.push(&key);
         }
-
-        self.container.append(&label);
-
-        let displayed = DisplayedKey {
-            key: key.key,
-            last_active: Instant::now(),
-            is_held: true,
-            label,
-        };
-
-        self.displayed_keys.push_back(displayed);
     }
 
     pub fn remove_key(&mut self, key: &KeyDisplay) {
         self.held_keys.remove(&key.key);
 
-        if let Some(displayed) = self.displayed_keys.iter_mut().find(|dk| dk.key == key.key) {
-            displayed.is_held = false;
-            displayed.last_active = Instant::now();
-            displayed.label.add_css_class("fading");
+        for group in self.groups.iter_mut() {
+            if let Some(displayed) = group.keys.iter_mut().find(|k| k.key == key.key) {
+                displayed.is_held = false;
+                group.last_active = Instant::now();
+                if !group.is_held() {
+                    group.container.add_css_class("fading");
+                }
+            }
+        }
+
+        if self.held_keys.is_empty() {
+            self.last_empty = Some(Instant::now());
         }
     }
 
@@ -112,54 +200,60 @@ impl KeyDisplayWidget {
         let display_duration = self.display_duration;
 
         let expired: Vec<usize> = self
-            .displayed_keys
+            .groups
             .iter()
             .enumerate()
-            .filter(|(_, dk)| !dk.is_held && now.duration_since(dk.last_active) > display_duration)
+            .filter(|(_, g)| !g.is_held() && now.duration_since(g.last_active) > display_duration)
             .map(|(i, _)| i)
             .collect();
 
         for &i in expired.iter().rev() {
-            if let Some(removed) = self.displayed_keys.remove(i) {
-                self.container.remove(&removed.label);
+            if let Some(removed) = self.groups.remove(i) {
+                self.container.remove(&removed.container);
             }
         }
 
-        if !expired.is_empty() {
-            self.cleanup_separators();
+        if self
+            .flash_until
+            .is_some_and(|until| now >= until)
+        {
+            self.flash_until = None;
+            self.container.remove_css_class("layout-flash");
         }
     }
 
     pub fn clear(&mut self) {
-        while let Some(child) = self.container.first_child() {
-            self.container.remove(&child);
+        while let Some(group) = self.groups.pop_front() {
+            self.container.remove(&group.container);
         }
-        self.displayed_keys.clear();
         self.held_keys.clear();
+        self.last_empty = None;
     }
 
-    fn cleanup_separators(&self) {
-        let mut child = self.container.first_child();
-        while let Some(widget) = child {
-            let next = widget.next_sibling();
-            if widget.has_css_class("keystroke-separator") {
-                self.container.remove(&widget);
-            }
-            child = next;
+    /// Show the active layout badge (e.g. `DV` for US-Dvorak) ahead of the
+    /// keys, or hide it when `indicator` is empty.
+    #[allow(dead_code)]
+    pub fn set_layout_indicator(&self, indicator: &str) {
+        if indicator.is_empty() {
+            self.layout_label.set_visible(false);
+        } else {
+            self.layout_label.set_text(indicator);
+            self.layout_label.set_visible(true);
         }
+    }
 
-        if self.displayed_keys.len() > 1 {
-            let mut child = self.container.first_child();
-            while let Some(widget) = child {
-                let next = widget.next_sibling();
-                if next.is_some() && !widget.has_css_class("keystroke-separator") {
-                    let separator = Label::new(Some("+"));
-                    separator.add_css_class("keystroke-separator");
-                    separator.insert_after(&self.container, Some(&widget));
-                }
-                child = next;
-            }
-        }
+    /// Briefly highlight the overlay to announce a layout switch. The flash
+    /// keeps the window on screen for [`FLASH_DURATION`] even with no keys held;
+    /// [`Self::is_flashing`] reports whether it is still active.
+    pub fn flash(&mut self) {
+        self.flash_until = Some(Instant::now() + FLASH_DURATION);
+        self.container.add_css_class("layout-flash");
+    }
+
+    #[must_use]
+    pub fn is_flashing(&self) -> bool {
+        self.flash_until
+            .is_some_and(|until| Instant::now() < until)
     }
 
     #[allow(dead_code)]
@@ -171,15 +265,15 @@ impl KeyDisplayWidget {
     pub fn set_max_keys(&mut self, max_keys: usize) {
         self.max_keys = max_keys;
 
-        while self.displayed_keys.len() > max_keys {
-            if let Some(old) = self.displayed_keys.pop_front() {
-                self.container.remove(&old.label);
+        while self.groups.len() > max_keys {
+            if let Some(old) = self.groups.pop_front() {
+                self.container.remove(&old.container);
             }
         }
     }
 
     #[allow(dead_code)]
     pub fn has_keys(&self) -> bool {
-        !self.displayed_keys.is_empty()
+        !self.groups.is_empty()
     }
 }
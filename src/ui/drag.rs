@@ -1,3 +1,5 @@
+use crate::config::{Config, Position};
+use gtk4::gdk;
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, GestureClick, GestureDrag};
 use gtk4_layer_shell::{Edge, LayerShell};
@@ -23,7 +25,7 @@ impl Default for DragState {
     }
 }
 
-pub fn setup_drag(window: &ApplicationWindow) {
+pub fn setup_drag(window: &ApplicationWindow, config: &Config) {
     window.set_anchor(Edge::Top, true);
     window.set_anchor(Edge::Left, true);
     window.set_anchor(Edge::Bottom, false);
@@ -33,6 +35,9 @@ pub fn setup_drag(window: &ApplicationWindow) {
     window.set_margin(Edge::Top, DEFAULT_MARGIN_Y);
 
     let drag_state = Rc::new(DragState::default());
+    let snap_enabled = config.snap_enabled;
+    let snap_threshold = config.snap_threshold.max(0);
+    let config = Rc::new(config.clone());
 
     let gesture = GestureDrag::new();
     gesture.set_button(1);
@@ -58,15 +63,43 @@ pub fn setup_drag(window: &ApplicationWindow) {
         let start_x = state.start_x.get();
         let start_y = state.start_y.get();
 
-        let new_x = (start_x + offset_x as i32).max(0);
-        let new_y = (start_y + offset_y as i32).max(0);
+        let mut new_x = (start_x + offset_x as i32).max(0);
+        let mut new_y = (start_y + offset_y as i32).max(0);
+
+        if snap_enabled {
+            if let Some((sx, sy, _)) = snap_targets(&win, new_x, new_y, snap_threshold) {
+                new_x = sx;
+                new_y = sy;
+            }
+        }
 
         win.set_margin(Edge::Left, new_x);
         win.set_margin(Edge::Top, new_y);
     });
 
+    let win = window.clone();
     gesture.connect_drag_end(move |_, offset_x, offset_y| {
         debug!("Drag ended with offset ({}, {})", offset_x, offset_y);
+
+        if !snap_enabled {
+            return;
+        }
+
+        let left = win.margin(Edge::Left);
+        let top = win.margin(Edge::Top);
+
+        if let Some((sx, sy, position)) = snap_targets(&win, left, top, snap_threshold) {
+            win.set_margin(Edge::Left, sx);
+            win.set_margin(Edge::Top, sy);
+
+            let mut persisted = (*config).clone();
+            persisted.position = position;
+            if let Err(e) = persisted.save() {
+                debug!("Failed to persist snapped position: {}", e);
+            } else {
+                debug!("Snapped to {:?} and persisted", position);
+            }
+        }
     });
 
     window.add_controller(gesture);
@@ -83,3 +116,78 @@ pub fn setup_drag(window: &ApplicationWindow) {
     });
     window.add_controller(click);
 }
+
+/// Given the proposed top-left margins, return snapped `(left, top)` margins and
+/// the resolved [`Position`]. An axis within `threshold` of a screen edge or
+/// center snaps to it; an axis outside it keeps its proposed margin, so a result
+/// is returned even when nothing snapped. Anchors stay pinned to top-left, so
+/// snapping is purely a margin adjustment. Returns `None` only when the monitor
+/// geometry is unavailable.
+fn snap_targets(
+    window: &ApplicationWindow,
+    left: i32,
+    top: i32,
+    threshold: i32,
+) -> Option<(i32, i32, Position)> {
+    let surface = window.surface()?;
+    let monitor = gdk::Display::default()?.monitor_at_surface(&surface)?;
+    let geometry = monitor.geometry();
+
+    let mon_w = geometry.width();
+    let mon_h = geometry.height();
+    let win_w = window.width().max(1);
+    let win_h = window.height().max(1);
+
+    let right = mon_w - win_w - left;
+    let center_x = (mon_w - win_w) / 2;
+    let bottom = mon_h - win_h - top;
+    let center_y = (mon_h - win_h) / 2;
+
+    let (snap_x, horizontal) = if left <= threshold {
+        (0, Horizontal::Left)
+    } else if right <= threshold {
+        ((mon_w - win_w).max(0), Horizontal::Right)
+    } else if (left - center_x).abs() <= threshold {
+        (center_x.max(0), Horizontal::Center)
+    } else {
+        (left, Horizontal::Center)
+    };
+
+    let (snap_y, vertical) = if top <= threshold {
+        (0, Vertical::Top)
+    } else if bottom <= threshold {
+        ((mon_h - win_h).max(0), Vertical::Bottom)
+    } else if (top - center_y).abs() <= threshold {
+        // No vertical-center Position exists; snap to the nearer edge for
+        // persistence while keeping the window centered visually.
+        (center_y.max(0), if top <= center_y { Vertical::Top } else { Vertical::Bottom })
+    } else {
+        (top, if top <= center_y { Vertical::Top } else { Vertical::Bottom })
+    };
+
+    Some((snap_x, snap_y, resolve_position(horizontal, vertical)))
+}
+
+#[derive(Clone, Copy)]
+enum Horizontal {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy)]
+enum Vertical {
+    Top,
+    Bottom,
+}
+
+fn resolve_position(horizontal: Horizontal, vertical: Vertical) -> Position {
+    match (vertical, horizontal) {
+        (Vertical::Top, Horizontal::Left) => Position::TopLeft,
+        (Vertical::Top, Horizontal::Center) => Position::TopCenter,
+        (Vertical::Top, Horizontal::Right) => Position::TopRight,
+        (Vertical::Bottom, Horizontal::Left) => Position::BottomLeft,
+        (Vertical::Bottom, Horizontal::Center) => Position::BottomCenter,
+        (Vertical::Bottom, Horizontal::Right) => Position::BottomRight,
+    }
+}
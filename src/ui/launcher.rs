@@ -1,6 +1,10 @@
 use gtk4::prelude::*;
 use gtk4::Box as GtkBox;
-use gtk4::{Application, ApplicationWindow, Button, CssProvider, Label, Orientation};
+use gtk4::{
+    Application, ApplicationWindow, Button, CssProvider, EventControllerKey, Label, Orientation,
+};
+use gtk4::gdk::Key;
+use gtk4::glib;
 use gtk4_layer_shell::{Edge, Layer, LayerShell};
 use std::cell::Cell;
 use std::rc::Rc;
@@ -45,6 +49,11 @@ const LAUNCHER_CSS: &str = r#"
     border-color: @accent_bg_color;
 }
 
+.launcher-button:focus {
+    border-color: @accent_bg_color;
+    outline: none;
+}
+
 .launcher-button:active {
     opacity: 0.8;
 }
@@ -87,7 +96,7 @@ pub fn create_launcher_window(
 
     window.set_namespace("keystroke-launcher");
 
-    window.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::None);
+    window.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::OnDemand);
 
     window.set_anchor(Edge::Top, true);
     window.set_anchor(Edge::Left, true);
@@ -173,6 +182,35 @@ fn create_launcher_content(
 
     container.append(&button_box);
 
+    let key_controller = EventControllerKey::new();
+    let win = window.clone();
+    let first = keystroke_btn.clone();
+    let second = bubble_btn.clone();
+    key_controller.connect_key_pressed(move |_, key, _, _| match key {
+        Key::Left | Key::Right | Key::Tab | Key::ISO_Left_Tab => {
+            if second.has_focus() {
+                first.grab_focus();
+            } else {
+                second.grab_focus();
+            }
+            glib::Propagation::Stop
+        }
+        Key::Return | Key::KP_Enter | Key::space => {
+            if second.has_focus() {
+                second.emit_clicked();
+            } else {
+                first.emit_clicked();
+            }
+            glib::Propagation::Stop
+        }
+        Key::Escape => {
+            win.set_visible(false);
+            glib::Propagation::Stop
+        }
+        _ => glib::Propagation::Proceed,
+    });
+    container.add_controller(key_controller);
+
     container
 }
 
@@ -213,6 +251,27 @@ fn setup_launcher_drag(window: &ApplicationWindow) {
 pub fn show_launcher(window: &ApplicationWindow) {
     window.set_visible(true);
     window.present();
+
+    if let Some(first) = first_launcher_button(window) {
+        first.grab_focus();
+    }
+}
+
+/// Walk the launcher's widget tree to the first mode button so it can take
+/// keyboard focus when the launcher is shown.
+fn first_launcher_button(window: &ApplicationWindow) -> Option<Button> {
+    let container = window.child()?;
+    let mut child = container.first_child();
+    while let Some(widget) = child {
+        if let Some(button_box) = widget.downcast_ref::<GtkBox>() {
+            if let Some(first) = button_box.first_child().and_then(|w| w.downcast::<Button>().ok())
+            {
+                return Some(first);
+            }
+        }
+        child = widget.next_sibling();
+    }
+    None
 }
 
 #[allow(dead_code)]
@@ -1,9 +1,17 @@
+pub mod bubble;
 pub mod display;
 pub mod drag;
 pub mod launcher;
+pub mod monitor;
+pub mod settings;
+pub mod stylesheet;
+pub mod theme;
 pub mod window;
 
+pub use bubble::BubbleDisplayWidget;
 pub use display::KeyDisplayWidget;
 pub use drag::setup_drag;
 pub use launcher::{create_launcher_window, show_launcher, DisplayMode};
-pub use window::create_window;
+pub use monitor::follow_focused_output;
+pub use settings::{create_settings_window, show_settings};
+pub use window::{apply_runtime_config, create_window};
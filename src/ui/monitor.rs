@@ -0,0 +1,153 @@
+use crate::compositor::{self, create_client};
+use crate::config::Config;
+use gtk4::gdk;
+use gtk4::prelude::*;
+use gtk4::ApplicationWindow;
+use gtk4_layer_shell::LayerShell;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Keep the overlay where the user is actually typing on a multi-monitor
+/// session. A non-empty `output` setting decides first: a connector name or
+/// index pins the overlay, and `focused` follows focus. Otherwise a pinned
+/// output wins; failing that, when follow-focus is enabled and the compositor
+/// streams focus events, the overlay is moved to the newly focused monitor. Margins are anchored relative to the active monitor,
+/// so they are preserved across a move.
+pub fn follow_focused_output(window: &ApplicationWindow, config: &Config) {
+    // The unified `output` control takes precedence when set; an empty value
+    // falls through to the legacy `pinned_output` / `follow_focus` pair.
+    match OutputSpec::parse(&config.output) {
+        Some(OutputSpec::Index(idx)) => {
+            if let Some(monitor) = find_monitor_by_index(window, idx) {
+                window.set_monitor(Some(&monitor));
+                info!("Pinned overlay to output index {}", idx);
+            } else {
+                warn!("Output index {} out of range; using default output", idx);
+            }
+            return;
+        }
+        Some(OutputSpec::Connector(name)) => {
+            if let Some(monitor) = find_monitor(window, &name) {
+                window.set_monitor(Some(&monitor));
+                info!("Pinned overlay to output {}", name);
+            } else {
+                warn!("Output {} not found; using default output", name);
+            }
+            return;
+        }
+        Some(OutputSpec::Focused) => {
+            follow_focus_events(window);
+            return;
+        }
+        None => {}
+    }
+
+    if !config.pinned_output.is_empty() {
+        if let Some(monitor) = find_monitor(window, &config.pinned_output) {
+            window.set_monitor(Some(&monitor));
+            info!("Pinned overlay to output {}", config.pinned_output);
+        } else {
+            warn!("Pinned output {} not found", config.pinned_output);
+        }
+        return;
+    }
+
+    if !config.follow_focus {
+        return;
+    }
+
+    follow_focus_events(window);
+}
+
+/// How the configured `output` string resolves to a monitor.
+enum OutputSpec {
+    /// A connector name such as `DP-1`.
+    Connector(String),
+
+    /// A zero-based index into [`gdk::Display::monitors`].
+    Index(u32),
+
+    /// Track the monitor holding the focused window.
+    Focused,
+}
+
+impl OutputSpec {
+    /// Parse the `output` config value, returning `None` when it is empty so the
+    /// caller can fall back to the legacy controls.
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+
+        if value.eq_ignore_ascii_case("focused") {
+            return Some(Self::Focused);
+        }
+
+        match value.parse::<u32>() {
+            Ok(index) => Some(Self::Index(index)),
+            Err(_) => Some(Self::Connector(value.to_string())),
+        }
+    }
+}
+
+/// Re-bind the layer surface to the focused monitor as the compositor reports
+/// focus changes. A no-op on compositors that don't stream focus events.
+fn follow_focus_events(window: &ApplicationWindow) {
+    let compositor = compositor::detect();
+
+    if !compositor.supports_focus_events() {
+        debug!("Compositor {} does not stream focus events", compositor);
+        return;
+    }
+
+    let Some(client) = create_client(compositor) else {
+        debug!("No compositor client available for {}", compositor);
+        return;
+    };
+
+    let receiver = match client.subscribe_focus() {
+        Ok(rx) => rx,
+        Err(e) => {
+            warn!("Failed to subscribe to focus events: {}", e);
+            return;
+        }
+    };
+
+    let window = window.clone();
+
+    glib::timeout_add_local(Duration::from_millis(100), move || {
+        while let Ok(connector) = receiver.try_recv() {
+            if let Some(monitor) = find_monitor(&window, &connector) {
+                debug!("Following focus to output {}", connector);
+                window.set_monitor(Some(&monitor));
+            }
+        }
+
+        glib::ControlFlow::Continue
+    });
+
+    info!("Following focused output on {}", compositor);
+}
+
+/// Resolve a monitor by its zero-based index on the window's display.
+fn find_monitor_by_index(window: &ApplicationWindow, index: u32) -> Option<gdk::Monitor> {
+    let monitors = gtk4::prelude::WidgetExt::display(window).monitors();
+    monitors.item(index)?.downcast::<gdk::Monitor>().ok()
+}
+
+/// Resolve a monitor by its connector name (e.g. `DP-1`) on the window's
+/// display.
+fn find_monitor(window: &ApplicationWindow, connector: &str) -> Option<gdk::Monitor> {
+    let monitors = gtk4::prelude::WidgetExt::display(window).monitors();
+
+    for i in 0..monitors.n_items() {
+        let monitor = monitors.item(i)?.downcast::<gdk::Monitor>().ok()?;
+
+        if monitor.connector().as_deref() == Some(connector) {
+            return Some(monitor);
+        }
+    }
+
+    None
+}
@@ -1,13 +1,19 @@
-use crate::config::{Config, Position};
+use crate::config::{Config, Density, Position};
+use crate::ui::stylesheet::{self, user_css_path};
+use crate::ui::theme::{themes_dir, Theme, Variant};
+use gtk4::gio;
+use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{
     Adjustment, Application, ApplicationWindow, Box as GtkBox, Button, ColorDialog,
-    ColorDialogButton, CssProvider, DropDown, Entry, Label, Orientation, Scale, SpinButton, Stack,
-    StackSidebar, StringList, Switch,
+    ColorDialogButton, CssProvider, DropDown, Entry, FileDialog, FileFilter, Label, ListBox,
+    Orientation, Scale, SelectionMode, SpinButton, Stack, StackSidebar, StringList, Switch,
+    ToggleButton,
 };
 use std::cell::RefCell;
+use std::fs;
 use std::rc::Rc;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 const SETTINGS_CSS: &str = r#"
 .settings-window {
@@ -248,7 +254,120 @@ const POSITION_OPTIONS: [(&str, Position); 6] = [
     ("Bottom Right", Position::BottomRight),
 ];
 
-const THEME_OPTIONS: [&str; 3] = ["Light", "Dark", "System"];
+thread_local! {
+    /// The provider holding the currently-applied theme's `@define-color`
+    /// overrides, kept so selecting a different theme replaces it rather than
+    /// stacking another provider on top of it.
+    static THEME_PROVIDER: RefCell<Option<CssProvider>> = const { RefCell::new(None) };
+    /// Name of the theme currently applied, so the system color-scheme watcher
+    /// can re-resolve it into the newly-active variant without a save.
+    static CURRENT_THEME: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// Guards one-time installation of the `StyleManager` dark-mode watcher.
+    static SCHEME_WATCH_INSTALLED: RefCell<bool> = const { RefCell::new(false) };
+}
+
+/// The variant the desktop is currently asking for, read from the GTK
+/// `gtk-application-prefer-dark-theme` setting.
+fn system_variant() -> Variant {
+    let dark = gtk4::Settings::default()
+        .map(|s| s.is_gtk_application_prefer_dark_theme())
+        .unwrap_or(false);
+    Variant::from_dark(dark)
+}
+
+/// Resolve `name` against the presets in the config directory and push its
+/// `@define-color` overrides onto `display`, removing whatever theme was applied
+/// before. The light or dark variant is chosen to match the system color scheme.
+/// Unknown names (including the previous Light/Dark/System literals) are treated
+/// as "no overrides", leaving the stock palette in place.
+fn apply_theme(display: &gtk4::gdk::Display, name: &str) {
+    CURRENT_THEME.with(|cur| *cur.borrow_mut() = Some(name.to_string()));
+    install_scheme_watch(display);
+
+    THEME_PROVIDER.with(|slot| {
+        if let Some(old) = slot.borrow_mut().take() {
+            gtk4::style_context_remove_provider_for_display(display, &old);
+        }
+
+        let theme = themes_dir()
+            .map(|dir| dir.join(format!("{name}.json")))
+            .filter(|p| p.exists())
+            .and_then(|p| Theme::load_file(&p).ok());
+
+        if let Some(theme) = theme {
+            let provider = CssProvider::new();
+            provider.load_from_string(&theme.to_css_defines(system_variant()));
+            gtk4::style_context_add_provider_for_display(
+                display,
+                &provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_USER,
+            );
+            *slot.borrow_mut() = Some(provider);
+        }
+    });
+}
+
+/// Subscribe to the desktop color-scheme signal so that, whenever the user
+/// toggles dark mode, the active preset is re-applied in the matching variant
+/// without a restart. Installed once, for the first display passed in; later
+/// calls are no-ops.
+fn install_scheme_watch(display: &gtk4::gdk::Display) {
+    SCHEME_WATCH_INSTALLED.with(|done| {
+        if *done.borrow() {
+            return;
+        }
+        *done.borrow_mut() = true;
+
+        let Some(settings) = gtk4::Settings::default() else {
+            return;
+        };
+        let display = display.clone();
+        settings.connect_gtk_application_prefer_dark_theme_notify(move |_| {
+            if let Some(name) = CURRENT_THEME.with(|cur| cur.borrow().clone()) {
+                apply_theme(&display, &name);
+            }
+        });
+    });
+}
+
+/// GSettings schema id; matches the application id and the installed gschema.
+const APP_ID: &str = "dev.linuxmobile.keystroke";
+
+thread_local! {
+    /// Provider carrying the live `@define-color bubble_bg_color` override, so a
+    /// GSettings `bubble-color` change recolors active bubbles in place.
+    static BUBBLE_PROVIDER: RefCell<Option<CssProvider>> = const { RefCell::new(None) };
+}
+
+/// Open the application's [`gio::Settings`], or `None` when the schema is not
+/// installed (e.g. running uninstalled without `GSETTINGS_SCHEMA_DIR` set), in
+/// which case callers fall back to the on-disk [`Config`].
+fn gsettings() -> Option<gio::Settings> {
+    let source = gio::SettingsSchemaSource::default()?;
+    source.lookup(APP_ID, true)?;
+    Some(gio::Settings::new(APP_ID))
+}
+
+/// Push `hex` into the live bubble provider so running overlays recolor without
+/// a restart.
+fn apply_bubble_color(hex: &str) {
+    let Some(display) = gtk4::gdk::Display::default() else {
+        return;
+    };
+    BUBBLE_PROVIDER.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let provider = slot.get_or_insert_with(|| {
+            let provider = CssProvider::new();
+            gtk4::style_context_add_provider_for_display(
+                &display,
+                &provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_USER,
+            );
+            provider
+        });
+        provider.load_from_string(&format!("@define-color bubble_bg_color {hex};\n"));
+    });
+}
 
 pub fn create_settings_window(
     app: &Application,
@@ -263,7 +382,7 @@ pub fn create_settings_window(
         .resizable(true)
         .build();
 
-    apply_settings_css(&window);
+    apply_settings_css(&window, config.borrow().density);
     window.add_css_class("settings-window");
 
     let main_box = GtkBox::builder()
@@ -289,6 +408,85 @@ pub fn create_settings_window(
     let bubble_page = create_bubble_page(&config_ref);
     stack.add_titled(&bubble_page.container, Some("bubble"), "Bubble");
 
+    let themes_page = create_themes_page(&config_ref, &window);
+    stack.add_titled(&themes_page.container, Some("themes"), "Themes");
+
+    // Density changes are a pure restyle, so regenerate the override provider
+    // live as the dropdown flips rather than waiting for save.
+    let density_display = gtk4::prelude::WidgetExt::display(&window);
+    keystroke_page
+        .density_dropdown
+        .connect_selected_notify(move |dd| {
+            if let Some((_, density)) = DENSITY_OPTIONS.get(dd.selected() as usize) {
+                set_density(&density_display, *density);
+            }
+        });
+
+    let preset_display = gtk4::prelude::WidgetExt::display(&window);
+    bubble_page
+        .preset_dropdown
+        .connect_selected_notify(move |dd| {
+            if let Some((_, preset)) = BUBBLE_PRESET_OPTIONS.get(dd.selected() as usize) {
+                set_bubble_preset(&preset_display, *preset);
+            }
+        });
+
+    // Live-preview the dimensional bubble rules as any of the numeric scales
+    // move, reading all five adjustments plus the shadow switch and colour each
+    // time.
+    let metrics_display = gtk4::prelude::WidgetExt::display(&window);
+    let opacity_adj = bubble_page.opacity_adj.clone();
+    let radius_adj = bubble_page.corner_radius_adj.clone();
+    let font_adj = bubble_page.font_size_adj.clone();
+    let shadow_adj = bubble_page.shadow_blur_adj.clone();
+    let shadow_offset_adj = bubble_page.shadow_offset_adj.clone();
+    let shadow_switch = bubble_page.shadow_switch.clone();
+    let shadow_color_button = bubble_page.shadow_color_button.clone();
+    let update_metrics = move || {
+        let rgba = shadow_color_button.rgba();
+        set_bubble_metrics(
+            &metrics_display,
+            BubbleMetrics {
+                opacity: opacity_adj.value(),
+                corner_radius: radius_adj.value(),
+                font_size: font_adj.value(),
+                shadow_enabled: shadow_switch.is_active(),
+                shadow_offset: shadow_offset_adj.value(),
+                shadow_blur: shadow_adj.value(),
+                shadow_color: (
+                    (rgba.red() * 255.0) as u8,
+                    (rgba.green() * 255.0) as u8,
+                    (rgba.blue() * 255.0) as u8,
+                    rgba.alpha() as f64,
+                ),
+            },
+        );
+    };
+    update_metrics();
+    for adj in [
+        &bubble_page.opacity_adj,
+        &bubble_page.corner_radius_adj,
+        &bubble_page.font_size_adj,
+        &bubble_page.shadow_blur_adj,
+        &bubble_page.shadow_offset_adj,
+    ] {
+        let update_metrics = update_metrics.clone();
+        adj.connect_value_changed(move |_| update_metrics());
+    }
+    // Toggling the shadow rebuilds the full sheet (see `BubbleMetrics::to_css`),
+    // so enabling and disabling it never leaves a dangling colour reference.
+    let toggle_update = update_metrics.clone();
+    bubble_page
+        .shadow_switch
+        .connect_state_set(move |_, _| {
+            toggle_update();
+            glib::Propagation::Proceed
+        });
+    let color_update = update_metrics.clone();
+    bubble_page
+        .shadow_color_button
+        .connect_rgba_notify(move |_| color_update());
+
     main_box.append(&sidebar);
 
     let content_box = GtkBox::builder()
@@ -333,13 +531,13 @@ pub fn create_settings_window(
 
     let window_clone = window.clone();
     let config_clone = config.clone();
+    let selected_theme = themes_page.selected.clone();
 
     save_btn.connect_clicked(move |_| {
-        let theme_idx = keystroke_page.theme_dropdown.selected();
-        let theme = THEME_OPTIONS
-            .get(theme_idx as usize)
-            .unwrap_or(&"System")
-            .to_lowercase();
+        let theme = selected_theme
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| "system".to_string());
 
         let ks_pos_idx = keystroke_page.position_dropdown.selected();
         let ks_position = POSITION_OPTIONS
@@ -368,6 +566,11 @@ pub fn create_settings_window(
             position: ks_position,
             keystroke_draggable: keystroke_page.draggable_switch.is_active(),
             keystroke_hotkey: keystroke_page.hotkey_entry.text().to_string(),
+            accent_color: keystroke_page.accent.borrow().clone(),
+            density: DENSITY_OPTIONS
+                .get(keystroke_page.density_dropdown.selected() as usize)
+                .map(|(_, d)| *d)
+                .unwrap_or(Density::Default),
 
             bubble_color: color_hex,
             bubble_font_size: bubble_page.font_size_adj.value(),
@@ -377,6 +580,9 @@ pub fn create_settings_window(
             bubble_position: b_position,
             bubble_draggable: bubble_page.draggable_switch.is_active(),
             bubble_timeout_ms: (bubble_page.duration_adj.value() * 1000.0) as u64,
+            opacity: bubble_page.opacity_adj.value(),
+            bubble_corner_radius: bubble_page.corner_radius_adj.value(),
+            bubble_shadow_blur: bubble_page.shadow_blur_adj.value(),
 
             ..config_clone.borrow().clone()
         };
@@ -397,14 +603,20 @@ pub fn create_settings_window(
 
 struct KeystrokeWidgets {
     container: GtkBox,
-    theme_dropdown: DropDown,
     duration_adj: Adjustment,
     max_keys_adj: Adjustment,
     position_dropdown: DropDown,
     draggable_switch: Switch,
     hotkey_entry: Entry,
+    /// Accent colour picked from the swatch row, shared with the overlays for
+    /// modifier highlights.
+    accent: Rc<RefCell<String>>,
+    density_dropdown: DropDown,
 }
 
+const DENSITY_OPTIONS: [(&str, Density); 2] =
+    [("Default", Density::Default), ("Compact", Density::Compact)];
+
 fn create_keystroke_page(config: &Config) -> KeystrokeWidgets {
     let container = GtkBox::builder()
         .orientation(Orientation::Vertical)
@@ -422,17 +634,6 @@ fn create_keystroke_page(config: &Config) -> KeystrokeWidgets {
         .css_classes(["settings-card"])
         .build();
 
-    let (theme_row, theme_dropdown) = create_dropdown_row(
-        "Theme Style",
-        Some("Light, Dark, or follow system theme"),
-        &THEME_OPTIONS,
-        THEME_OPTIONS
-            .iter()
-            .position(|&t| t.to_lowercase() == config.keystroke_theme)
-            .unwrap_or(2) as u32,
-    );
-    appearance_card.append(&theme_row);
-
     let (duration_row, duration_adj) = create_scale_row(
         "Duration",
         Some("How long keystrokes stay visible"),
@@ -453,6 +654,39 @@ fn create_keystroke_page(config: &Config) -> KeystrokeWidgets {
     );
     appearance_card.append(&max_keys_row);
 
+    let accent = Rc::new(RefCell::new(config.accent_color.clone()));
+    let accent_row = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .css_classes(["settings-row"])
+        .build();
+    let accent_label_box = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .hexpand(true)
+        .valign(gtk4::Align::Center)
+        .build();
+    accent_label_box.append(
+        &Label::builder()
+            .label("Accent Color")
+            .halign(gtk4::Align::Start)
+            .css_classes(["settings-label"])
+            .build(),
+    );
+    accent_label_box.append(
+        &Label::builder()
+            .label("Highlight colour for modifier keys")
+            .halign(gtk4::Align::Start)
+            .css_classes(["settings-sublabel"])
+            .build(),
+    );
+    let accent_pick = accent.clone();
+    let accent_swatches = create_swatch_row(Some(&config.accent_color), move |hex| {
+        *accent_pick.borrow_mut() = hex.to_string();
+    });
+    accent_row.append(&accent_label_box);
+    accent_row.append(&accent_swatches);
+    appearance_card.append(&accent_row);
+
     container.append(&appearance_card);
 
     add_section_title(&container, "Position & Behavior");
@@ -488,16 +722,29 @@ fn create_keystroke_page(config: &Config) -> KeystrokeWidgets {
     );
     position_card.append(&hotkey_row);
 
+    let current_density_idx = DENSITY_OPTIONS
+        .iter()
+        .position(|(_, d)| *d == config.density)
+        .unwrap_or(0) as u32;
+    let (density_row, density_dropdown) = create_dropdown_row(
+        "Density",
+        Some("Compact shrinks padding for small displays"),
+        &DENSITY_OPTIONS.map(|(n, _)| n),
+        current_density_idx,
+    );
+    position_card.append(&density_row);
+
     container.append(&position_card);
 
     KeystrokeWidgets {
         container,
-        theme_dropdown,
         duration_adj,
         max_keys_adj,
         position_dropdown,
         draggable_switch,
         hotkey_entry,
+        accent,
+        density_dropdown,
     }
 }
 
@@ -511,6 +758,13 @@ struct BubbleWidgets {
     position_dropdown: DropDown,
     draggable_switch: Switch,
     duration_adj: Adjustment,
+    preset_dropdown: DropDown,
+    opacity_adj: Adjustment,
+    corner_radius_adj: Adjustment,
+    shadow_blur_adj: Adjustment,
+    shadow_switch: Switch,
+    shadow_color_button: ColorDialogButton,
+    shadow_offset_adj: Adjustment,
 }
 
 fn create_bubble_page(config: &Config) -> BubbleWidgets {
@@ -530,6 +784,14 @@ fn create_bubble_page(config: &Config) -> BubbleWidgets {
         .css_classes(["settings-card"])
         .build();
 
+    let (preset_row, preset_dropdown) = create_dropdown_row(
+        "Appearance Preset",
+        Some("Coordinated light/dark styling; custom colors override it"),
+        &BUBBLE_PRESET_OPTIONS.map(|(n, _)| n),
+        2,
+    );
+    appearance_card.append(&preset_row);
+
     let (color_row, color_button) =
         create_color_row("Bubble Color", Some("Background color of bubbles"), config);
     appearance_card.append(&color_row);
@@ -545,6 +807,61 @@ fn create_bubble_page(config: &Config) -> BubbleWidgets {
     );
     appearance_card.append(&size_row);
 
+    let (opacity_row, opacity_adj) = create_scale_row(
+        "Opacity",
+        Some("Bubble transparency"),
+        config.opacity,
+        0.1,
+        1.0,
+        0.05,
+        "",
+    );
+    appearance_card.append(&opacity_row);
+
+    let (radius_row, corner_radius_adj) = create_scale_row(
+        "Corner Radius",
+        Some("Roundness of bubble corners"),
+        config.bubble_corner_radius,
+        0.0,
+        48.0,
+        1.0,
+        "px",
+    );
+    appearance_card.append(&radius_row);
+
+    let (shadow_toggle_row, shadow_switch) = create_switch_row(
+        "Drop Shadow",
+        Some("Cast a shadow behind bubbles"),
+        config.bubble_shadow_blur > 0.0,
+    );
+    appearance_card.append(&shadow_toggle_row);
+
+    let (shadow_color_row, shadow_color_button) =
+        create_shadow_color_row("Shadow Color", Some("Color of the drop shadow"));
+    appearance_card.append(&shadow_color_row);
+
+    let (shadow_offset_row, shadow_offset_adj) = create_scale_row(
+        "Shadow Offset",
+        Some("Vertical offset of the drop shadow"),
+        2.0,
+        0.0,
+        24.0,
+        1.0,
+        "px",
+    );
+    appearance_card.append(&shadow_offset_row);
+
+    let (shadow_row, shadow_blur_adj) = create_scale_row(
+        "Shadow Blur",
+        Some("Drop-shadow blur radius"),
+        config.bubble_shadow_blur,
+        0.0,
+        48.0,
+        1.0,
+        "px",
+    );
+    appearance_card.append(&shadow_row);
+
     let (font_row, font_entry) = create_entry_row(
         "Font Family",
         Some("Font used in bubbles"),
@@ -626,9 +943,258 @@ fn create_bubble_page(config: &Config) -> BubbleWidgets {
         position_dropdown,
         draggable_switch,
         duration_adj,
+        preset_dropdown,
+        opacity_adj,
+        corner_radius_adj,
+        shadow_blur_adj,
+        shadow_switch,
+        shadow_color_button,
+        shadow_offset_adj,
+    }
+}
+
+struct ThemesWidgets {
+    container: GtkBox,
+    /// Name of the preset the user last selected, written back into
+    /// [`Config::keystroke_theme`] on save. Starts as the configured theme.
+    selected: Rc<RefCell<Option<String>>>,
+}
+
+fn create_themes_page(config: &Config, window: &ApplicationWindow) -> ThemesWidgets {
+    let container = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(0)
+        .css_classes(["settings-content-area"])
+        .build();
+
+    let header = create_page_header("Themes", "Color presets loaded from your config directory");
+    container.append(&header);
+
+    let selected = Rc::new(RefCell::new(Some(config.keystroke_theme.clone())));
+    let themes = themes_dir().map(|d| Theme::load_dir(&d)).unwrap_or_default();
+
+    let variant_label = match system_variant() {
+        Variant::Light => "Following system color scheme · light variant active",
+        Variant::Dark => "Following system color scheme · dark variant active",
+    };
+    let variant_note = Label::builder()
+        .label(variant_label)
+        .halign(gtk4::Align::Start)
+        .css_classes(["settings-sublabel"])
+        .build();
+    container.append(&variant_note);
+
+    add_section_title(&container, "Presets");
+
+    let list_card = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .css_classes(["settings-card"])
+        .build();
+
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::Single)
+        .css_classes(["settings-theme-list"])
+        .build();
+
+    if themes.is_empty() {
+        let empty = Label::builder()
+            .label("No theme presets found. Import one or drop a *.json file in the themes directory.")
+            .wrap(true)
+            .halign(gtk4::Align::Start)
+            .css_classes(["settings-sublabel"])
+            .build();
+        list_card.append(&empty);
+    } else {
+        let names: Vec<String> = themes.iter().map(|t| t.name.clone()).collect();
+        for name in &names {
+            let row_label = Label::builder()
+                .label(name)
+                .halign(gtk4::Align::Start)
+                .css_classes(["settings-label"])
+                .build();
+            list.append(&row_label);
+        }
+
+        if let Some(idx) = selected
+            .borrow()
+            .as_ref()
+            .and_then(|cur| names.iter().position(|n| n == cur))
+        {
+            if let Some(row) = list.row_at_index(idx as i32) {
+                list.select_row(Some(&row));
+            }
+        }
+
+        let selected_row = selected.clone();
+        let display = gtk4::prelude::WidgetExt::display(window);
+        list.connect_row_selected(move |_, row| {
+            if let Some(row) = row {
+                let idx = row.index();
+                if let Some(name) = names.get(idx as usize) {
+                    *selected_row.borrow_mut() = Some(name.clone());
+                    apply_theme(&display, name);
+                }
+            }
+        });
+
+        list_card.append(&list);
+    }
+
+    container.append(&list_card);
+
+    add_section_title(&container, "Share");
+
+    let share_card = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .css_classes(["settings-card"])
+        .build();
+
+    let import_btn = Button::with_label("Import…");
+    let export_btn = Button::with_label("Export…");
+    export_btn.add_css_class("suggested-action");
+    share_card.append(&import_btn);
+    share_card.append(&export_btn);
+    container.append(&share_card);
+
+    let window_import = window.clone();
+    import_btn.connect_clicked(move |_| {
+        let dialog = FileDialog::builder()
+            .title("Import Theme")
+            .filters(&theme_file_filter())
+            .build();
+        let window = window_import.clone();
+        dialog.open(Some(&window), gtk4::gio::Cancellable::NONE, move |res| {
+            let Ok(file) = res else { return };
+            let Some(src) = file.path() else { return };
+            let Some(dir) = themes_dir() else { return };
+            if let Err(e) = fs::create_dir_all(&dir) {
+                warn!("Failed to create themes dir {:?}: {}", dir, e);
+                return;
+            }
+            let dest = src
+                .file_name()
+                .map(|n| dir.join(n))
+                .unwrap_or_else(|| dir.join("imported.json"));
+            if let Err(e) = fs::copy(&src, &dest) {
+                warn!("Failed to import theme {:?}: {}", src, e);
+            } else {
+                info!("Imported theme to {:?}", dest);
+            }
+        });
+    });
+
+    let window_export = window.clone();
+    let selected_export = selected.clone();
+    export_btn.connect_clicked(move |_| {
+        let Some(name) = selected_export.borrow().clone() else {
+            return;
+        };
+        let theme = themes_dir()
+            .map(|d| d.join(format!("{name}.json")))
+            .filter(|p| p.exists())
+            .and_then(|p| Theme::load_file(&p).ok());
+        let Some(theme) = theme else { return };
+
+        let dialog = FileDialog::builder()
+            .title("Export Theme")
+            .initial_name(format!("{name}.json"))
+            .filters(&theme_file_filter())
+            .build();
+        let window = window_export.clone();
+        dialog.save(Some(&window), gtk4::gio::Cancellable::NONE, move |res| {
+            let Ok(file) = res else { return };
+            let Some(dest) = file.path() else { return };
+            if let Err(e) = theme.export(&dest) {
+                warn!("Failed to export theme to {:?}: {}", dest, e);
+            } else {
+                info!("Exported theme to {:?}", dest);
+            }
+        });
+    });
+
+    add_section_title(&container, "Custom CSS");
+
+    let css_card = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .css_classes(["settings-card"])
+        .build();
+
+    let (css_row, css_switch) = create_switch_row(
+        "Custom Stylesheet",
+        Some("Load custom.css from the config directory, overriding the built-in theme"),
+        user_css_path().map(|p| p.exists()).unwrap_or(false),
+    );
+    css_card.append(&css_row);
+
+    css_switch.connect_state_set(|_, state| {
+        stylesheet::set_enabled(state);
+        glib::Propagation::Proceed
+    });
+
+    let open_row = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .css_classes(["settings-row"])
+        .build();
+    let open_label = Label::builder()
+        .label("Open the stylesheet in your editor to restyle the overlays live")
+        .wrap(true)
+        .hexpand(true)
+        .halign(gtk4::Align::Start)
+        .css_classes(["settings-sublabel"])
+        .build();
+    let open_btn = Button::with_label("Open stylesheet");
+    open_btn.set_valign(gtk4::Align::Center);
+    open_btn.set_halign(gtk4::Align::End);
+    open_row.append(&open_label);
+    open_row.append(&open_btn);
+    css_card.append(&open_row);
+
+    open_btn.connect_clicked(|_| {
+        open_user_stylesheet();
+    });
+
+    container.append(&css_card);
+
+    ThemesWidgets {
+        container,
+        selected,
     }
 }
 
+/// Create `custom.css` (seeded with a hint comment) if it is missing, then hand
+/// it to the desktop's default handler for editing.
+fn open_user_stylesheet() {
+    let Some(path) = user_css_path() else {
+        return;
+    };
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let template = "/* Custom keystroke styling. Overrides the built-in theme.\n   Use the @theme_* named colors, e.g.:\n   .keystroke-key { border-radius: 4px; } */\n";
+        if let Err(e) = fs::write(&path, template) {
+            warn!("Failed to create stylesheet {:?}: {}", path, e);
+            return;
+        }
+    }
+    let uri = format!("file://{}", path.display());
+    if let Err(e) = gio::AppInfo::launch_default_for_uri(&uri, gio::AppLaunchContext::NONE) {
+        warn!("Failed to open stylesheet {:?}: {}", path, e);
+    }
+}
+
+/// A `*.json`-only file filter list shared by the import and export dialogs.
+fn theme_file_filter() -> gio::ListStore {
+    let filter = FileFilter::new();
+    filter.set_name(Some("Theme files"));
+    filter.add_suffix("json");
+    let filters = gio::ListStore::new::<FileFilter>();
+    filters.append(&filter);
+    filters
+}
+
 fn create_page_header(title: &str, subtitle: &str) -> GtkBox {
     let header = GtkBox::builder()
         .orientation(Orientation::Horizontal)
@@ -916,11 +1482,112 @@ fn create_scale_row(
     (row, adj)
 }
 
+/// A fixed libadwaita-style named palette rendered as the swatch row. Grouped
+/// by hue with a light/mid/dark shade each, matching the `@blue_2`/`@purple_3`
+/// conventions used across GTK themes.
+const ACCENT_SWATCHES: [&str; 24] = [
+    "#62a0ea", "#3584e4", "#1c71d8", // blue
+    "#57e389", "#33d17a", "#26a269", // green
+    "#f9f06b", "#f6d32d", "#e5a50a", // yellow
+    "#ffbe6f", "#ff7800", "#e66100", // orange
+    "#f66151", "#e01b24", "#c01c28", // red
+    "#dc8add", "#c061cb", "#9141ac", // purple
+    "#f8aefe", "#d16d9e", "#9c5476", // pink
+    "#9a9996", "#5e5c64", "#3d3846", // slate
+];
+
+thread_local! {
+    /// Guards one-time installation of the per-swatch background rules, which
+    /// colour the `.color-button-row` buttons the stylesheet already styles.
+    static SWATCH_CSS_INSTALLED: RefCell<bool> = const { RefCell::new(false) };
+}
+
+/// CSS class carrying a swatch's background colour, derived from its hex so the
+/// generated rule and the button agree.
+fn swatch_class(hex: &str) -> String {
+    format!("swatch-{}", hex.trim_start_matches('#'))
+}
+
+/// Install the `.color-button-row button.swatch-* { background-color }` rules
+/// once, on the first display passed in, so every swatch paints its colour.
+/// Later calls are no-ops.
+fn install_swatch_css(display: &gtk4::gdk::Display) {
+    SWATCH_CSS_INSTALLED.with(|done| {
+        if *done.borrow() {
+            return;
+        }
+        let mut css = String::new();
+        for hex in ACCENT_SWATCHES {
+            css.push_str(&format!(
+                ".color-button-row button.{} {{ background-color: {}; }}\n",
+                swatch_class(hex),
+                hex
+            ));
+        }
+        let provider = CssProvider::new();
+        provider.load_from_string(&css);
+        gtk4::style_context_add_provider_for_display(
+            display,
+            &provider,
+            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+        *done.borrow_mut() = true;
+    });
+}
+
+/// A row of round toggle-button swatches. Exactly one can be active at a time;
+/// clicking one invokes `on_pick` with its hex. `initial` pre-selects the
+/// matching swatch when the current colour is one of the presets.
+fn create_swatch_row(initial: Option<&str>, on_pick: impl Fn(&str) + 'static) -> GtkBox {
+    let row = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk4::Align::End)
+        .css_classes(["color-button-row"])
+        .build();
+
+    let on_pick = Rc::new(on_pick);
+    let mut group: Option<ToggleButton> = None;
+
+    for hex in ACCENT_SWATCHES {
+        let button = ToggleButton::builder()
+            .css_classes([swatch_class(hex)])
+            .tooltip_text(hex)
+            .valign(gtk4::Align::Center)
+            .build();
+
+        match &group {
+            Some(first) => button.set_group(Some(first)),
+            None => group = Some(button.clone()),
+        }
+
+        if initial.is_some_and(|c| c.eq_ignore_ascii_case(hex)) {
+            button.set_active(true);
+        }
+
+        let on_pick = on_pick.clone();
+        button.connect_toggled(move |b| {
+            if b.is_active() {
+                on_pick(hex);
+            }
+        });
+
+        row.append(&button);
+    }
+
+    row
+}
+
 fn create_color_row(
     label: &str,
     sublabel: Option<&str>,
     config: &Config,
 ) -> (GtkBox, ColorDialogButton) {
+    let container = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(8)
+        .build();
+
     let row = GtkBox::builder()
         .orientation(Orientation::Horizontal)
         .spacing(12)
@@ -954,28 +1621,391 @@ fn create_color_row(
     color_button.set_valign(gtk4::Align::Center);
     color_button.set_halign(gtk4::Align::End);
 
-    if let Ok(rgba) = gtk4::gdk::RGBA::parse(&config.bubble_color) {
+    // Prefer the GSettings value so the control reflects any live change from
+    // another instance; fall back to the on-disk config when the schema is
+    // absent.
+    let initial_color = gsettings()
+        .map(|s| s.string("bubble-color").to_string())
+        .unwrap_or_else(|| config.bubble_color.clone());
+    if let Ok(rgba) = gtk4::gdk::RGBA::parse(&initial_color) {
         color_button.set_rgba(&rgba);
     }
+    apply_bubble_color(&initial_color);
+
+    if let Some(settings) = gsettings() {
+        // Edits to the button write straight back to the schema, which fans the
+        // change out to every watcher.
+        let settings_write = settings.clone();
+        color_button.connect_rgba_notify(move |btn| {
+            let rgba = btn.rgba();
+            let hex = format!(
+                "#{:02x}{:02x}{:02x}",
+                (rgba.red() * 255.0) as u8,
+                (rgba.green() * 255.0) as u8,
+                (rgba.blue() * 255.0) as u8
+            );
+            if settings_write.string("bubble-color") != hex {
+                let _ = settings_write.set_string("bubble-color", &hex);
+            }
+        });
+
+        // Re-render active bubbles whenever the schema changes, regardless of
+        // which instance made the edit.
+        settings.connect_changed(Some("bubble-color"), move |s, _| {
+            let hex = s.string("bubble-color");
+            apply_bubble_color(&hex);
+        });
+    }
+
+    row.append(&label_box);
+    row.append(&color_button);
+    container.append(&row);
+
+    // One-click recolouring from the named palette; clicking a swatch drives the
+    // same ColorDialogButton so the save path stays a single source of truth.
+    let color_button_swatch = color_button.clone();
+    let swatches = create_swatch_row(Some(&config.bubble_color), move |hex| {
+        if let Ok(rgba) = gtk4::gdk::RGBA::parse(hex) {
+            color_button_swatch.set_rgba(&rgba);
+        }
+    });
+    container.append(&swatches);
+
+    (container, color_button)
+}
+
+/// A plain colour row (no GSettings binding) used for the drop-shadow colour,
+/// defaulting to opaque black.
+fn create_shadow_color_row(label: &str, sublabel: Option<&str>) -> (GtkBox, ColorDialogButton) {
+    let row = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .css_classes(["settings-row"])
+        .build();
+
+    let label_box = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .hexpand(true)
+        .valign(gtk4::Align::Center)
+        .build();
+
+    let label_w = Label::builder()
+        .label(label)
+        .halign(gtk4::Align::Start)
+        .css_classes(["settings-label"])
+        .build();
+    label_box.append(&label_w);
+
+    if let Some(sub) = sublabel {
+        label_box.append(
+            &Label::builder()
+                .label(sub)
+                .halign(gtk4::Align::Start)
+                .css_classes(["settings-sublabel"])
+                .build(),
+        );
+    }
+
+    let color_button = ColorDialogButton::new(Some(ColorDialog::new()));
+    color_button.set_valign(gtk4::Align::Center);
+    color_button.set_halign(gtk4::Align::End);
+    color_button.set_rgba(&gtk4::gdk::RGBA::new(0.0, 0.0, 0.0, 0.4));
 
     row.append(&label_box);
     row.append(&color_button);
     (row, color_button)
 }
 
-fn apply_settings_css(window: &ApplicationWindow) {
-    let provider = CssProvider::new();
-    provider.load_from_string(SETTINGS_CSS);
+/// The numeric size-variant values density mode parameterizes, rather than
+/// hardcoding them in the stylesheet. Compact shrinks the always-on-top HUD and
+/// settings UI for small displays.
+struct DensityMetrics {
+    row_padding: &'static str,
+    row_min_height: u32,
+    card_radius: u32,
+    entry_padding: &'static str,
+    entry_radius: u32,
+    button_padding: &'static str,
+    key_padding: &'static str,
+    key_radius: u32,
+}
+
+impl DensityMetrics {
+    fn for_density(density: Density) -> Self {
+        match density {
+            Density::Default => DensityMetrics {
+                row_padding: "16px 20px",
+                row_min_height: 48,
+                card_radius: 12,
+                entry_padding: "8px 12px",
+                entry_radius: 6,
+                button_padding: "8px 20px",
+                key_padding: "8px 14px",
+                key_radius: 8,
+            },
+            Density::Compact => DensityMetrics {
+                row_padding: "8px 12px",
+                row_min_height: 32,
+                card_radius: 8,
+                entry_padding: "4px 8px",
+                entry_radius: 4,
+                button_padding: "4px 12px",
+                key_padding: "4px 8px",
+                key_radius: 5,
+            },
+        }
+    }
+}
+
+thread_local! {
+    /// Provider holding the density-dependent rule overrides, reloaded in place
+    /// when the density switch flips.
+    static DENSITY_PROVIDER: RefCell<Option<CssProvider>> = const { RefCell::new(None) };
+}
+
+/// Generate the size-variant rules for `density`, layered over the base sheet so
+/// they override the row/card/entry/button and overlay key-cap metrics.
+fn density_css(density: Density) -> String {
+    let m = DensityMetrics::for_density(density);
+    format!(
+        ".settings-row {{ padding: {rp}; min-height: {rh}px; }}\n\
+         .settings-card {{ border-radius: {cr}px; }}\n\
+         .flat-entry {{ padding: {ep}; border-radius: {er}px; }}\n\
+         .suggested-action, .cancel-button {{ padding: {bp}; }}\n\
+         .keystroke-key {{ padding: {kp}; border-radius: {kr}px; }}\n",
+        rp = m.row_padding,
+        rh = m.row_min_height,
+        cr = m.card_radius,
+        ep = m.entry_padding,
+        er = m.entry_radius,
+        bp = m.button_padding,
+        kp = m.key_padding,
+        kr = m.key_radius,
+    )
+}
+
+/// Apply (or re-apply) the density overrides to `display`. Installed just above
+/// the application sheets so it wins over their defaults but stays below the
+/// user stylesheet.
+fn set_density(display: &gtk4::gdk::Display, density: Density) {
+    DENSITY_PROVIDER.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let provider = slot.get_or_insert_with(|| {
+            let provider = CssProvider::new();
+            gtk4::style_context_add_provider_for_display(
+                display,
+                &provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+            );
+            provider
+        });
+        provider.load_from_string(&density_css(density));
+    });
+}
+
+/// Bubble appearance preset. Light and Dark pin the base stylesheet variant;
+/// Follow System mirrors the desktop `prefer-dark` setting and swaps at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BubblePreset {
+    Light,
+    Dark,
+    FollowSystem,
+}
+
+const BUBBLE_PRESET_OPTIONS: [(&str, BubblePreset); 3] = [
+    ("Light", BubblePreset::Light),
+    ("Dark", BubblePreset::Dark),
+    ("Follow System", BubblePreset::FollowSystem),
+];
+
+impl BubblePreset {
+    /// Whether this preset currently resolves to the dark base stylesheet.
+    fn is_dark(self) -> bool {
+        match self {
+            BubblePreset::Light => false,
+            BubblePreset::Dark => true,
+            BubblePreset::FollowSystem => system_variant() == Variant::Dark,
+        }
+    }
+}
+
+/// Dark-variant overrides layered onto [`SETTINGS_CSS`] when a dark preset is
+/// active. Kept as an override block rather than a whole second sheet so the two
+/// cannot drift apart.
+const SETTINGS_CSS_DARK: &str = r#"
+@define-color window_bg_color #1e1e2e;
+@define-color card_bg_color #313244;
+@define-color bubble_bg_color #241f31;
+@define-color bubble_fg_color #ffffff;
+@define-color border_color #45475a;
+"#;
+
+thread_local! {
+    /// The base settings stylesheet provider, swapped in place when the bubble
+    /// preset changes between the light and dark variants.
+    static BASE_PROVIDER: RefCell<Option<CssProvider>> = const { RefCell::new(None) };
+    /// Guards the one-time prefer-dark watch for Follow System.
+    static BUBBLE_PRESET_WATCH: RefCell<bool> = const { RefCell::new(false) };
+}
+
+/// Apply `preset` by reloading the base stylesheet in the matching variant. For
+/// Follow System a one-time watch re-applies the preset when the desktop color
+/// scheme toggles. Custom color picks still win, as they sit in the
+/// higher-priority [`BUBBLE_PROVIDER`].
+fn set_bubble_preset(display: &gtk4::gdk::Display, preset: BubblePreset) {
+    BASE_PROVIDER.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let provider = slot.get_or_insert_with(|| {
+            let provider = CssProvider::new();
+            gtk4::style_context_add_provider_for_display(
+                display,
+                &provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+            provider
+        });
+        let mut css = String::from(SETTINGS_CSS);
+        if preset.is_dark() {
+            css.push_str(SETTINGS_CSS_DARK);
+        }
+        provider.load_from_string(&css);
+    });
+
+    if preset == BubblePreset::FollowSystem {
+        BUBBLE_PRESET_WATCH.with(|done| {
+            if *done.borrow() {
+                return;
+            }
+            *done.borrow_mut() = true;
+            if let Some(settings) = gtk4::Settings::default() {
+                let display = display.clone();
+                settings.connect_gtk_application_prefer_dark_theme_notify(move |_| {
+                    set_bubble_preset(&display, BubblePreset::FollowSystem);
+                });
+            }
+        });
+    }
+}
+
+thread_local! {
+    /// Provider carrying the generated numeric bubble rules (opacity, corner
+    /// radius, font size, shadow), reloaded in place as the scales move.
+    static BUBBLE_METRICS_PROVIDER: RefCell<Option<CssProvider>> = const { RefCell::new(None) };
+}
+
+/// The dimensional bubble values tuned by the Scale rows, injected into a
+/// generated stylesheet rather than hardcoded in [`SETTINGS_CSS`].
+#[derive(Debug, Clone, Copy)]
+struct BubbleMetrics {
+    opacity: f64,
+    corner_radius: f64,
+    font_size: f64,
+    shadow_enabled: bool,
+    shadow_offset: f64,
+    shadow_blur: f64,
+    /// Shadow colour as straight RGBA bytes; always emitted as a literal.
+    shadow_color: (u8, u8, u8, f64),
+}
+
+impl BubbleMetrics {
+    fn to_css(self) -> String {
+        // The whole sheet is regenerated from scratch each time, so toggling the
+        // shadow off simply omits the `filter` line instead of removing a class
+        // that would leave a dangling unresolved colour behind — which is what
+        // trips GTK's `color->type == COLOR_TYPE_LITERAL` assertion. The shadow
+        // colour is always a fully-resolved `rgba()` literal, never a named or
+        // derived colour reference.
+        let filter = if self.shadow_enabled && self.shadow_blur > 0.0 {
+            let (r, g, b, a) = self.shadow_color;
+            format!(
+                "  filter: drop-shadow(0 {offset}px {blur}px rgba({r}, {g}, {b}, {a}));\n",
+                offset = self.shadow_offset,
+                blur = self.shadow_blur,
+            )
+        } else {
+            String::new()
+        };
 
+        format!(
+            ".bubble {{\n  \
+               opacity: {opacity};\n  \
+               border-radius: {radius}px;\n  \
+               font-size: {font}em;\n\
+             {filter}\
+             }}\n",
+            opacity = self.opacity,
+            radius = self.corner_radius,
+            font = self.font_size,
+        )
+    }
+}
+
+/// Apply (or re-apply) the generated numeric bubble rules to `display`.
+fn set_bubble_metrics(display: &gtk4::gdk::Display, metrics: BubbleMetrics) {
+    BUBBLE_METRICS_PROVIDER.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let provider = slot.get_or_insert_with(|| {
+            let provider = CssProvider::new();
+            gtk4::style_context_add_provider_for_display(
+                display,
+                &provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_USER,
+            );
+            provider
+        });
+        provider.load_from_string(&metrics.to_css());
+    });
+}
+
+fn apply_settings_css(window: &ApplicationWindow, density: Density) {
     let display = gtk4::prelude::WidgetExt::display(window);
+    install_swatch_css(&display);
 
-    gtk4::style_context_add_provider_for_display(
-        &display,
-        &provider,
-        gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-    );
+    set_bubble_preset(&display, BubblePreset::FollowSystem);
+
+    set_density(&display, density);
+    crate::ui::stylesheet::install(&display);
 }
 
 pub fn show_settings(window: &ApplicationWindow) {
     window.present();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(shadow_enabled: bool) -> BubbleMetrics {
+        BubbleMetrics {
+            opacity: 0.9,
+            corner_radius: 12.0,
+            font_size: 1.0,
+            shadow_enabled,
+            shadow_offset: 2.0,
+            shadow_blur: 8.0,
+            shadow_color: (0, 0, 0, 0.4),
+        }
+    }
+
+    #[test]
+    fn shadow_color_is_always_a_literal() {
+        // The shadow must be a resolved rgba() literal, never a named/derived
+        // color, or GTK aborts on COLOR_TYPE_LITERAL when the sheet reloads.
+        let css = metrics(true).to_css();
+        assert!(css.contains("drop-shadow(0 2px 8px rgba(0, 0, 0, 0.4))"));
+        assert!(!css.contains('@'));
+    }
+
+    #[test]
+    fn toggling_shadow_off_rebuilds_without_dangling_reference() {
+        // Enabling then disabling regenerates the whole sheet; the disabled sheet
+        // simply omits the filter rather than leaving a class behind.
+        let enabled = metrics(true).to_css();
+        let disabled = metrics(false).to_css();
+        assert!(enabled.contains("filter: drop-shadow"));
+        assert!(!disabled.contains("drop-shadow"));
+        assert!(!disabled.contains("filter"));
+        // Both remain valid, self-contained rules with no color reference.
+        assert!(!disabled.contains('@'));
+    }
+}
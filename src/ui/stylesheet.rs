@@ -0,0 +1,126 @@
+use gtk4::gdk::Display;
+use gtk4::gio;
+use gtk4::prelude::*;
+use gtk4::CssProvider;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Stylesheet files loaded from the config directory, in ascending priority. The
+/// legacy `custom.css` is kept for compatibility; `style.css` is the documented
+/// power-user sheet that overrides bubble shape, fonts, animations, and shadows.
+const STYLE_FILES: [&str; 2] = ["custom.css", "style.css"];
+
+thread_local! {
+    /// Handles to the user stylesheets so they are installed and watched
+    /// exactly once no matter how many windows ask for them.
+    static USER_STYLESHEETS: RefCell<Vec<UserStylesheet>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An optional user-authored stylesheet loaded from the config directory. It is
+/// layered on top of the built-in overlay and settings sheets at
+/// [`gtk4::STYLE_PROVIDER_PRIORITY_USER`], so it wins over them without the user
+/// editing source, and it may reference the same `@theme_*` named colors.
+struct UserStylesheet {
+    path: PathBuf,
+    provider: CssProvider,
+    // The monitor must outlive installation for `changed` to keep firing.
+    _monitor: gio::FileMonitor,
+}
+
+/// Path of the legacy user stylesheet: `~/.config/keystroke/custom.css`.
+pub fn user_css_path() -> Option<PathBuf> {
+    style_path("custom.css")
+}
+
+fn style_path(name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("keystroke").join(name))
+}
+
+/// Install the user stylesheets for `display` and watch them for changes so
+/// edits to colors, border-radius, padding, fonts, and the `scale`/`switch`/
+/// `dropdown` rules take effect live. Installs only once — repeated calls, for
+/// this or any other display, are a no-op once the watches are in place.
+pub fn install(display: &Display) {
+    USER_STYLESHEETS.with(|slot| {
+        if !slot.borrow().is_empty() {
+            return;
+        }
+
+        let mut sheets = Vec::new();
+        for name in STYLE_FILES {
+            let Some(path) = style_path(name) else {
+                continue;
+            };
+
+            let provider = CssProvider::new();
+            reload(&provider, &path);
+            gtk4::style_context_add_provider_for_display(
+                display,
+                &provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_USER,
+            );
+
+            let file = gio::File::for_path(&path);
+            let monitor =
+                match file.monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE) {
+                    Ok(monitor) => monitor,
+                    Err(e) => {
+                        warn!("Could not watch user stylesheet {:?}: {}", path, e);
+                        continue;
+                    }
+                };
+
+            let provider_watch = provider.clone();
+            let path_watch = path.clone();
+            monitor.connect_changed(move |_, _, _, event| {
+                if matches!(
+                    event,
+                    gio::FileMonitorEvent::Changed
+                        | gio::FileMonitorEvent::Created
+                        | gio::FileMonitorEvent::Deleted
+                ) {
+                    reload(&provider_watch, &path_watch);
+                }
+            });
+
+            sheets.push(UserStylesheet {
+                path,
+                provider,
+                _monitor: monitor,
+            });
+        }
+
+        *slot.borrow_mut() = sheets;
+    });
+}
+
+/// Toggle the user stylesheets on or off without tearing down the watches. When
+/// disabled the providers are emptied so only the built-in sheets apply;
+/// enabling re-reads each file.
+pub fn set_enabled(enabled: bool) {
+    USER_STYLESHEETS.with(|slot| {
+        for sheet in slot.borrow().iter() {
+            if enabled {
+                reload(&sheet.provider, &sheet.path);
+            } else {
+                sheet.provider.load_from_string("");
+            }
+        }
+    });
+}
+
+/// Load the stylesheet file into `provider`, clearing it when the file is absent
+/// or unreadable so a deleted custom sheet reverts to the built-in styling.
+fn reload(provider: &CssProvider, path: &Path) {
+    match std::fs::read_to_string(path) {
+        Ok(css) => {
+            debug!("Loaded user stylesheet from {:?}", path);
+            provider.load_from_string(&css);
+        }
+        Err(e) => {
+            debug!("No user stylesheet at {:?}: {}", path, e);
+            provider.load_from_string("");
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::{debug, warn};
+
+/// The named colors a theme preset is expected to define. The overlay and
+/// settings stylesheets reference these as `@name`, so each of them a preset
+/// defines is resolved to a concrete value before being handed to GTK. A
+/// variable the preset leaves out is not emitted.
+pub const THEME_VARIABLES: [&str; 7] = [
+    "accent_bg_color",
+    "accent_fg_color",
+    "window_bg_color",
+    "card_bg_color",
+    "bubble_bg_color",
+    "bubble_fg_color",
+    "border_color",
+];
+
+/// Built-in libadwaita-style palette, addressable from preset files as
+/// `@purple_3`, `@blue_2`, etc.
+static PALETTE: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    HashMap::from([
+        ("blue_1", "#99c1f1"),
+        ("blue_2", "#62a0ea"),
+        ("blue_3", "#3584e4"),
+        ("blue_4", "#1c71d8"),
+        ("blue_5", "#1a5fb4"),
+        ("green_2", "#57e389"),
+        ("green_3", "#33d17a"),
+        ("yellow_3", "#f6d32d"),
+        ("orange_3", "#ff7800"),
+        ("red_3", "#e01b24"),
+        ("purple_2", "#c061cb"),
+        ("purple_3", "#9141ac"),
+        ("pink_3", "#d16d9e"),
+        ("slate_3", "#5e5c64"),
+        ("light_1", "#ffffff"),
+        ("light_4", "#deddda"),
+        ("dark_3", "#3d3846"),
+        ("dark_5", "#241f31"),
+    ])
+});
+
+/// Which resolved palette a theme should hand to GTK. "System" themes follow
+/// the desktop color scheme and swap between these at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Light,
+    Dark,
+}
+
+impl Variant {
+    /// The variant for the given desktop dark-mode preference, defaulting to
+    /// light when the desktop expresses none.
+    pub fn from_dark(is_dark: bool) -> Self {
+        if is_dark {
+            Variant::Dark
+        } else {
+            Variant::Light
+        }
+    }
+}
+
+/// The raw value of a preset variable: either one value shared by both variants
+/// or a distinct `{ "light": ..., "dark": ... }` pair, mirroring the
+/// `if($variant=='dark', ...)` asset pattern.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+enum VarValue {
+    Shared(String),
+    Variants { light: String, dark: String },
+}
+
+impl VarValue {
+    fn for_variant(&self, variant: Variant) -> &str {
+        match self {
+            VarValue::Shared(v) => v,
+            VarValue::Variants { light, dark } => match variant {
+                Variant::Light => light,
+                Variant::Dark => dark,
+            },
+        }
+    }
+}
+
+/// A resolved theme: a name plus a concrete hex value for each variable the
+/// preset file defines, in both a light and a dark variant.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    light: HashMap<String, String>,
+    dark: HashMap<String, String>,
+}
+
+impl Theme {
+    /// Load every `*.json` preset from `dir`, resolving palette references and
+    /// color functions. Unreadable or malformed files are skipped with a warning
+    /// rather than failing the whole load.
+    pub fn load_dir(dir: &Path) -> Vec<Theme> {
+        let mut themes = Vec::new();
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("No theme directory at {:?}: {}", dir, e);
+                return themes;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            match Theme::load_file(&path) {
+                Ok(theme) => themes.push(theme),
+                Err(e) => warn!("Skipping theme {:?}: {}", path, e),
+            }
+        }
+
+        themes.sort_by(|a, b| a.name.cmp(&b.name));
+        themes
+    }
+
+    pub fn load_file(path: &Path) -> Result<Theme> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let raw: HashMap<String, VarValue> =
+            serde_json::from_str(&content).context("Invalid theme JSON")?;
+
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("theme")
+            .to_string();
+
+        let light = resolve_variant(&raw, Variant::Light)?;
+        let dark = resolve_variant(&raw, Variant::Dark)?;
+
+        Ok(Theme { name, light, dark })
+    }
+
+    pub fn export(&self, path: &Path) -> Result<()> {
+        // Round-trip as a `{ light, dark }` pair per variable so a shared theme
+        // file carries both variants.
+        let mut out: HashMap<String, VarValue> = HashMap::new();
+        for var in THEME_VARIABLES {
+            if let (Some(light), Some(dark)) = (self.light.get(var), self.dark.get(var)) {
+                out.insert(
+                    var.to_string(),
+                    VarValue::Variants {
+                        light: light.clone(),
+                        dark: dark.clone(),
+                    },
+                );
+            }
+        }
+        let content = serde_json::to_string_pretty(&out).context("Failed to serialize")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    /// Emit `@define-color name #rrggbb;` lines for `variant` to prepend to a
+    /// generated stylesheet so the UI recolors from this theme.
+    pub fn to_css_defines(&self, variant: Variant) -> String {
+        let colors = match variant {
+            Variant::Light => &self.light,
+            Variant::Dark => &self.dark,
+        };
+        let mut out = String::new();
+        for var in THEME_VARIABLES {
+            if let Some(color) = colors.get(var) {
+                out.push_str(&format!("@define-color {} {};\n", var, color));
+            }
+        }
+        out
+    }
+}
+
+/// Resolve every variable for a single variant, picking the variant-specific
+/// raw value before resolving references and functions against the other
+/// variables of the same variant.
+fn resolve_variant(
+    raw: &HashMap<String, VarValue>,
+    variant: Variant,
+) -> Result<HashMap<String, String>> {
+    let vars: HashMap<String, String> = raw
+        .iter()
+        .map(|(k, v)| (k.clone(), v.for_variant(variant).to_string()))
+        .collect();
+
+    let mut colors = HashMap::new();
+    for (key, value) in &vars {
+        colors.insert(key.clone(), resolve_color(value, &vars)?);
+    }
+    Ok(colors)
+}
+
+/// Directory presets are loaded from: `~/.config/keystroke/themes`.
+pub fn themes_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("keystroke").join("themes"))
+}
+
+/// Resolve a single value, which may be a hex literal, a `@palette_ref`, a
+/// reference to another variable in the same preset, or a `mix()`/`shade()`
+/// function call.
+fn resolve_color(value: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let value = value.trim();
+
+    if let Some(rest) = value.strip_prefix('@') {
+        if let Some(hex) = PALETTE.get(rest) {
+            return Ok((*hex).to_string());
+        }
+        if let Some(referenced) = vars.get(rest) {
+            return resolve_color(referenced, vars);
+        }
+        anyhow::bail!("Unknown color reference '@{}'", rest);
+    }
+
+    if let Some(args) = value.strip_prefix("mix(").and_then(|s| s.strip_suffix(')')) {
+        let parts: Vec<&str> = args.splitn(3, ',').map(str::trim).collect();
+        if parts.len() != 3 {
+            anyhow::bail!("mix() expects 3 arguments");
+        }
+        let a = Rgb::parse(&resolve_color(parts[0], vars)?)?;
+        let b = Rgb::parse(&resolve_color(parts[1], vars)?)?;
+        let factor: f64 = parts[2].parse().context("mix() factor")?;
+        return Ok(a.mix(b, factor).to_hex());
+    }
+
+    if let Some(args) = value
+        .strip_prefix("shade(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        let parts: Vec<&str> = args.splitn(2, ',').map(str::trim).collect();
+        if parts.len() != 2 {
+            anyhow::bail!("shade() expects 2 arguments");
+        }
+        let c = Rgb::parse(&resolve_color(parts[0], vars)?)?;
+        let factor: f64 = parts[1].parse().context("shade() factor")?;
+        return Ok(c.shade(factor).to_hex());
+    }
+
+    Rgb::parse(value).map(|c| c.to_hex())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rgb {
+    r: f64,
+    g: f64,
+    b: f64,
+}
+
+impl Rgb {
+    fn parse(hex: &str) -> Result<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 {
+            anyhow::bail!("Expected #rrggbb, got '{}'", hex);
+        }
+        let channel = |i: usize| -> Result<f64> {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map(|v| v as f64 / 255.0)
+                .context("Invalid hex digit")
+        };
+        Ok(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+
+    fn mix(self, other: Rgb, factor: f64) -> Rgb {
+        let f = factor.clamp(0.0, 1.0);
+        Rgb {
+            r: self.r * (1.0 - f) + other.r * f,
+            g: self.g * (1.0 - f) + other.g * f,
+            b: self.b * (1.0 - f) + other.b * f,
+        }
+    }
+
+    fn shade(self, factor: f64) -> Rgb {
+        Rgb {
+            r: (self.r * factor).clamp(0.0, 1.0),
+            g: (self.g * factor).clamp(0.0, 1.0),
+            b: (self.b * factor).clamp(0.0, 1.0),
+        }
+    }
+
+    fn to_hex(self) -> String {
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            (self.r * 255.0).round() as u8,
+            (self.g * 255.0).round() as u8,
+            (self.b * 255.0).round() as u8
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty() -> HashMap<String, String> {
+        HashMap::new()
+    }
+
+    #[test]
+    fn test_resolve_literal_hex() {
+        assert_eq!(resolve_color("#3584e4", &empty()).unwrap(), "#3584e4");
+    }
+
+    #[test]
+    fn test_resolve_palette_reference() {
+        assert_eq!(resolve_color("@purple_3", &empty()).unwrap(), "#9141ac");
+    }
+
+    #[test]
+    fn test_resolve_mix() {
+        // Halfway between black and white is mid-grey.
+        let mixed = resolve_color("mix(#000000, #ffffff, 0.5)", &empty()).unwrap();
+        assert_eq!(mixed, "#808080");
+    }
+
+    #[test]
+    fn test_resolve_shade() {
+        let shaded = resolve_color("shade(#ffffff, 0.5)", &empty()).unwrap();
+        assert_eq!(shaded, "#808080");
+    }
+
+    #[test]
+    fn test_css_defines() {
+        let theme = Theme {
+            name: "t".to_string(),
+            light: HashMap::from([("accent_bg_color".to_string(), "#3584e4".to_string())]),
+            dark: HashMap::from([("accent_bg_color".to_string(), "#1a5fb4".to_string())]),
+        };
+        assert!(theme
+            .to_css_defines(Variant::Light)
+            .contains("@define-color accent_bg_color #3584e4;"));
+        assert!(theme
+            .to_css_defines(Variant::Dark)
+            .contains("@define-color accent_bg_color #1a5fb4;"));
+    }
+
+    #[test]
+    fn test_per_variable_variants() {
+        let raw: HashMap<String, VarValue> = serde_json::from_str(
+            r#"{ "window_bg_color": { "light": "#ffffff", "dark": "@dark_5" } }"#,
+        )
+        .unwrap();
+        let light = resolve_variant(&raw, Variant::Light).unwrap();
+        let dark = resolve_variant(&raw, Variant::Dark).unwrap();
+        assert_eq!(light["window_bg_color"], "#ffffff");
+        assert_eq!(dark["window_bg_color"], "#241f31");
+    }
+}
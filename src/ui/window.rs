@@ -38,11 +38,36 @@ const OVERLAY_CSS: &str = r#"
     opacity: 0.6;
 }
 
+.keystroke-chord {
+    border-radius: 10px;
+    border: 1px solid @borders;
+    padding: 2px 4px;
+}
+
+.keystroke-chord.fading {
+    opacity: 0.6;
+}
+
 .keystroke-separator {
     color: @window_fg_color;
     font-weight: bold;
     padding: 0 4px;
 }
+
+.keystroke-layout {
+    background-color: @accent_bg_color;
+    color: @accent_fg_color;
+    border-radius: 6px;
+    padding: 2px 8px;
+    margin-right: 6px;
+    font-size: 0.85em;
+    font-weight: bold;
+}
+
+.keystroke-container.layout-flash {
+    background-color: alpha(@accent_bg_color, 0.25);
+    border-radius: 12px;
+}
 "#;
 
 pub fn create_window(app: &Application, config: &Config) -> Result<ApplicationWindow> {
@@ -60,14 +85,7 @@ pub fn create_window(app: &Application, config: &Config) -> Result<ApplicationWi
 
     window.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::None);
 
-    for (edge, anchor) in config.position.layer_shell_edges() {
-        window.set_anchor(edge, anchor);
-    }
-
-    window.set_margin(Edge::Top, config.margin);
-    window.set_margin(Edge::Bottom, config.margin);
-    window.set_margin(Edge::Left, config.margin);
-    window.set_margin(Edge::Right, config.margin);
+    apply_runtime_config(&window, config);
 
     window.set_exclusive_zone(0);
 
@@ -83,6 +101,23 @@ pub fn create_window(app: &Application, config: &Config) -> Result<ApplicationWi
     Ok(window)
 }
 
+/// Apply the config values that can change at runtime — anchor edges, margins,
+/// and opacity — to an existing window. Called once at creation and again by
+/// the config hot-reload path so edits to `position`, `margin`, or `opacity`
+/// take effect without a restart.
+pub fn apply_runtime_config(window: &ApplicationWindow, config: &Config) {
+    for (edge, anchor) in config.position.layer_shell_edges() {
+        window.set_anchor(edge, anchor);
+    }
+
+    window.set_margin(Edge::Top, config.margin);
+    window.set_margin(Edge::Bottom, config.margin);
+    window.set_margin(Edge::Left, config.margin);
+    window.set_margin(Edge::Right, config.margin);
+
+    window.set_opacity(config.opacity);
+}
+
 fn apply_css(window: &ApplicationWindow) {
     let provider = CssProvider::new();
     provider.load_from_string(OVERLAY_CSS);
@@ -94,6 +129,8 @@ fn apply_css(window: &ApplicationWindow) {
         &provider,
         gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
+
+    crate::ui::stylesheet::install(&display);
 }
 
 #[allow(dead_code)]